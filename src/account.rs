@@ -0,0 +1,371 @@
+//! This module holds the status of the account behind the API key, as told by the key and credits
+//! endpoints of the OpenRouter API: the credit left, the rate limit of the key, and how many
+//! requests to free models it may make a day.
+//!
+//! The key is checked against these endpoints on start, so that a bad key is caught before the
+//! first round rather than in the middle of it. APIs other than the OpenRouter one usually lack
+//! them, in which case there's simply no account to tell about.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Deserialize;
+use ureq::Agent;
+
+use crate::backend::error::CompletionError;
+use crate::chat::ErrorResponse;
+use crate::secret::ApiKey;
+
+/// This constant holds the credit that must have been bought over the life of the account for free
+/// models to take the larger daily quota of requests, in dollars.
+const FREE_QUOTA_THRESHOLD: f64 = 10.0;
+
+/// This constant holds the number of requests to free models an account may make a day, before and
+/// after buying enough credit.
+const FREE_QUOTAS: (u32, u32) = (50, 1000);
+
+/// This structure holds the status of the account behind an API key.
+#[derive(Clone)]
+pub struct Account {
+    /// This field contains the credit bought and used over the life of the account, if the API
+    /// told.
+    credits: Option<Credits>,
+    /// This field contains the details of the API key itself.
+    key: Key,
+}
+
+/// This structure holds the credit bought and used over the life of the account, in dollars.
+#[derive(Clone, Copy, Deserialize)]
+struct Credits {
+    /// This field contains the credit bought.
+    total_credits: f64,
+    /// This field contains the credit used.
+    total_usage: f64,
+}
+
+/// This structure holds the body of a response from the key and credits endpoints, which wrap what
+/// they tell in a `data` object.
+#[derive(Deserialize)]
+struct Data<T> {
+    /// This field contains what the endpoint tells.
+    data: T,
+}
+
+/// This structure holds the details of an API key, as told by the key endpoint.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+struct Key {
+    /// This field contains whether the account has never bought any credit.
+    is_free_tier: bool,
+    /// This field contains the name given to the key, if any.
+    label: Option<String>,
+    /// This field contains the most the key may spend, in dollars, if it's limited.
+    limit: Option<f64>,
+    /// This field contains what the key may still spend, in dollars, if it's limited.
+    limit_remaining: Option<f64>,
+    /// This field contains the number of requests the key may make in every interval, if told.
+    rate_limit: Option<RateLimit>,
+    /// This field contains what the key has spent so far, in dollars.
+    usage: f64,
+}
+
+/// This structure holds the rate limit of an API key.
+#[derive(Clone, Deserialize)]
+struct RateLimit {
+    /// This field contains the interval the requests are counted over, e.g. `10s`.
+    interval: String,
+    /// This field contains the number of requests allowed in every interval.
+    requests: i64,
+}
+
+impl Account {
+    /// This function checks the given API key against the key endpoint of the API at the given base
+    /// URL, and returns the status of its account. Unless the key is rejected, failing to get the
+    /// status is no reason not to play, so there's simply no account to tell about when the API
+    /// can't be reached or has no such endpoint.
+    ///
+    /// # Errors
+    ///
+    /// The function returns an error if the API rejects the key, with the key taken out of it.
+    pub fn check(base_url: &str, api_key: &ApiKey) -> Result<Option<Self>> {
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(Duration::from_secs(10)))
+            .build()
+            .new_agent();
+
+        match Self::fetch(&agent, base_url, api_key) {
+            Ok(account) => Ok(account),
+            Err(err @ CompletionError::Auth(_)) => Err(err.into()),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// This function returns the credit left, in dollars: what the key may still spend if it's
+    /// limited, or what's left of the credit of the account otherwise, if either one is known.
+    fn credit_left(&self) -> Option<f64> {
+        self.key.limit_remaining.or_else(|| {
+            self.credits
+                .map(|credits| credits.total_credits - credits.total_usage)
+        })
+    }
+
+    /// This function returns the lines describing the account in the options menu.
+    pub(crate) fn details(&self) -> Vec<String> {
+        let mut details = Vec::new();
+
+        if let Some(ref label) = self.key.label {
+            details.push(format!("Key: {label}"));
+        }
+        if let Some(credits) = self.credits {
+            details.push(format!(
+                "Credit: ${:.4} left of ${:.4} bought",
+                credits.total_credits - credits.total_usage,
+                credits.total_credits
+            ));
+        }
+        details.push(match (self.key.limit, self.key.limit_remaining) {
+            (Some(limit), Some(remaining)) => {
+                format!("Key limit: ${remaining:.4} left of ${limit:.4}")
+            }
+            _ => "Key limit: none".to_owned(),
+        });
+        details.push(format!("Spent with this key: ${:.4}", self.key.usage));
+        details.push(format!("Tier: {}", self.tier()));
+        if let Some(rate_limit) = self.rate_limit() {
+            details.push(format!("Rate limit: {rate_limit}"));
+        }
+        if let Some(quota) = self.free_quota() {
+            details.push(format!("Free models: {quota} requests a day"));
+        }
+
+        details
+    }
+
+    /// This function fetches the status of the account behind the given API key from the API at
+    /// the given base URL through the given agent, or returns nothing if the API has no key
+    /// endpoint. The credits endpoint is only asked as a bonus, since it may be off limits to the
+    /// key.
+    pub(crate) fn fetch(
+        agent: &Agent,
+        base_url: &str,
+        api_key: &ApiKey,
+    ) -> Result<Option<Self>, CompletionError> {
+        let Some(key) = get(agent, &format!("{base_url}/key"), api_key)? else {
+            return Ok(None);
+        };
+        let credits = get(agent, &format!("{base_url}/credits"), api_key)
+            .ok()
+            .flatten();
+
+        Ok(Some(Self { credits, key }))
+    }
+
+    /// This function returns the number of requests to free models the account may make a day, if
+    /// it can be told from whether and how much credit the account has bought.
+    pub(crate) fn free_quota(&self) -> Option<u32> {
+        let (low, high) = FREE_QUOTAS;
+
+        if self.key.is_free_tier {
+            return Some(low);
+        }
+
+        self.credits.map(|credits| {
+            if credits.total_credits >= FREE_QUOTA_THRESHOLD {
+                high
+            } else {
+                low
+            }
+        })
+    }
+
+    /// This function returns the rate limit of the key as it's shown to the user, if told.
+    fn rate_limit(&self) -> Option<String> {
+        self.key.rate_limit.as_ref().map(|rate_limit| {
+            if rate_limit.requests < 0 {
+                "unlimited".to_owned()
+            } else {
+                format!(
+                    "{} requests every {}",
+                    rate_limit.requests, rate_limit.interval
+                )
+            }
+        })
+    }
+
+    /// This function returns the one-line summary of the account for the status bar, with the
+    /// credit left lowered by what the answers of the session have cost so far, in dollars, since
+    /// the status was fetched.
+    pub(crate) fn status(&self, spent: f64) -> String {
+        let mut status = Vec::new();
+
+        if let Some(left) = self.credit_left() {
+            status.push(format!("${:.2} left", (left - spent).max(0.0)));
+        }
+        status.push(self.tier().to_owned());
+        if let Some(rate_limit) = self.rate_limit() {
+            status.push(rate_limit);
+        }
+        if let Some(quota) = self.free_quota() {
+            status.push(format!("{quota} free requests a day"));
+        }
+
+        status.join(", ")
+    }
+
+    /// This function returns the tier of the account, which is the free one until the account buys
+    /// any credit.
+    const fn tier(&self) -> &'static str {
+        if self.key.is_free_tier {
+            "free tier"
+        } else {
+            "paid tier"
+        }
+    }
+}
+
+/// This function asks one of the account endpoints what it tells with the given API key, or
+/// returns nothing if the API has no such endpoint. The API key is taken out of any error, in case
+/// the API echoed it back.
+fn get<T>(agent: &Agent, url: &str, api_key: &ApiKey) -> Result<Option<T>, CompletionError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let response = agent
+        .get(url)
+        .header("Authorization", format!("Bearer {}", api_key.expose()))
+        .call()
+        .map_err(|err| CompletionError::from(err).redact(api_key))?;
+    let status = response.status().as_u16();
+
+    if status == 404 {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let message = response
+            .into_body()
+            .read_json::<ErrorResponse>()
+            .map(ErrorResponse::into_message)
+            .unwrap_or_default();
+        return Err(CompletionError::from_status(status, &message, None).redact(api_key));
+    }
+
+    response
+        .into_body()
+        .read_json::<Data<T>>()
+        .map(|body| Some(body.data))
+        .map_err(|err| CompletionError::from(err).redact(api_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead as _, BufReader, Write as _};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::worker;
+
+    /// This constant holds the API key the stand-in API is asked with.
+    const API_KEY: &str = "sk-or-v1-secret";
+
+    /// This function serves the given status and body to every request to the key and credits
+    /// endpoints of a stand-in API on a local port, and a 404 to any other request. It returns the
+    /// base URL of the stand-in API.
+    fn serve(status: u16, key: &'static str, credits: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("a local port should be free");
+        let address = listener
+            .local_addr()
+            .expect("the local port should be known");
+
+        drop(thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request = String::new();
+                let mut header = String::new();
+                if reader.read_line(&mut request).is_err() {
+                    continue;
+                }
+                while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+                    header.clear();
+                }
+
+                let (status, body) = match request.split_whitespace().nth(1) {
+                    Some("/key") => (status, key),
+                    Some("/credits") => (status, credits),
+                    _ => (404, "{}"),
+                };
+                let length = body.len();
+                let response = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Length: {length}\r\n\
+                    Connection: close\r\n\r\n{body}"
+                );
+                drop((&stream).write_all(response.as_bytes()));
+            }
+        }));
+
+        format!("http://{address}")
+    }
+
+    /// This function fetches the status of the account from the stand-in API at the given base URL.
+    fn fetch(base_url: &str) -> Result<Option<Account>, CompletionError> {
+        let api_key = ApiKey::new(API_KEY).expect("the API key shouldn't be empty");
+        Account::fetch(&worker::agent(), base_url, &api_key)
+    }
+
+    #[test]
+    fn accounts_are_parsed() {
+        let base_url = serve(
+            200,
+            r#"{"data": {"label": "sk-or-v1-abc...xyz", "limit": 5.0, "limit_remaining": 3.5,
+                "usage": 1.5, "is_free_tier": false,
+                "rate_limit": {"requests": 20, "interval": "10s"}}}"#,
+            r#"{"data": {"total_credits": 12.5, "total_usage": 2.25}}"#,
+        );
+        let account = fetch(&base_url)
+            .expect("the key should be accepted")
+            .expect("the account should be told about");
+
+        assert_eq!(
+            account.status(1.0),
+            "$2.50 left, paid tier, 20 requests every 10s, 1000 free requests a day",
+            "the status should tell the credit left to the key, less what the session spent"
+        );
+        assert_eq!(
+            account.details().first().map(String::as_str),
+            Some("Key: sk-or-v1-abc...xyz"),
+            "the details should start with the label of the key"
+        );
+    }
+
+    #[test]
+    fn rejected_keys_are_redacted() {
+        let base_url = serve(
+            401,
+            r#"{"error": {"code": 401, "message": "Invalid key: sk-or-v1-secret"}}"#,
+            "{}",
+        );
+        let Err(err) = fetch(&base_url) else {
+            panic!("the key should be rejected");
+        };
+
+        assert!(
+            matches!(err, CompletionError::Auth(_)),
+            "a 401 should reject the key: {err}"
+        );
+        assert!(
+            !err.to_string().contains(API_KEY),
+            "the key should be taken out of the error: {err}"
+        );
+    }
+
+    #[test]
+    fn missing_endpoints_make_for_no_account() {
+        let base_url = serve(404, "{}", "{}");
+
+        assert!(
+            fetch(&base_url).is_ok_and(|account| account.is_none()),
+            "an API without a key endpoint should have no account to tell about"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! This module holds the backends able to produce an answer to the result of a game.
+//!
+//! The game itself only knows about the [`ResponseBackend`] trait, so the OpenRouter API is just
+//! one more way of producing an answer, next to the offline, bank, mock and external command
+//! backends.
+
+mod banked;
+mod canned;
+mod chain;
+mod command;
+mod deck;
+pub(crate) mod error;
+mod fallback;
+mod mock;
+mod openrouter;
+mod retry;
+mod sanitized;
+mod structured;
+mod varied;
+
+use anyhow::Result;
+use ureq::Agent;
+
+use crate::account::Account;
+use crate::backend::banked::Banked;
+use crate::backend::canned::Canned;
+use crate::backend::chain::ModelChain;
+use crate::backend::command::ExternalCommand;
+use crate::backend::fallback::Fallback;
+use crate::backend::mock::Mock;
+use crate::backend::openrouter::OpenRouter;
+use crate::backend::sanitized::Sanitized;
+use crate::backend::structured::Structured;
+use crate::backend::varied::Varied;
+use crate::chat::{Request, Usage};
+use crate::game::RandomResult;
+use crate::mood::Tone;
+use crate::settings::{Backend, Settings};
+use crate::throttle::Throttle;
+
+/// This enumeration holds what a backend streaming its answer hands over to the sink.
+pub(crate) enum Piece<'text> {
+    /// This variant is used when the answer starts over, so that everything handed over before
+    /// is to be taken back.
+    Restart,
+    /// This variant is used for the next piece of the answer, to be added to the ones before.
+    Text(&'text str),
+}
+
+/// This trait implements methods for objects able to answer the result of a game.
+pub(crate) trait ResponseBackend
+where
+    Self: Send,
+{
+    /// This function returns whether the last answer was generated, by an LLM or by an external
+    /// command, rather than taken from the canned lines or the bank of answers. Backends that may
+    /// generate answers tell so; the rest never do.
+    fn is_generated(&self) -> bool {
+        false
+    }
+
+    /// This function returns the model the last answer was produced by, for those backends that
+    /// may produce it with a model other than the one in the request.
+    fn model(&self) -> Option<String> {
+        None
+    }
+
+    /// This function returns a short note about how the last answer was produced, to be shown next
+    /// to it, if there's anything worth noting.
+    fn notice(&self) -> Option<String> {
+        None
+    }
+    /// This function produces the answer to the result of a game. The request body holds the
+    /// chat exchange that would be sent to an LLM, for those backends interested in it.
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String>;
+
+    /// This function drops the models to fall back to that the given function doesn't keep, for
+    /// those backends that fall back to other models, and returns whether any was dropped.
+    fn retain_fallbacks(&mut self, _keep: &dyn Fn(&str) -> bool) -> bool {
+        false
+    }
+
+    /// This function takes note of the given answer having been shown in the voice of the persona
+    /// with the given identifier, for those backends that keep track of the answers shown.
+    fn shown(&mut self, _persona: &str, _answer: &str) {}
+
+    /// This function produces the answer to the result of a game piece by piece, handing every
+    /// piece over to the sink as soon as it's available, and returns the whole answer at the end.
+    /// Backends that find out the answer handed over so far was wrong tell the sink to start over.
+    ///
+    /// Backends that can't stream their answers hand over the whole answer at once.
+    fn stream(
+        &mut self,
+        request: &Request,
+        result: RandomResult,
+        sink: &mut dyn FnMut(Piece<'_>),
+    ) -> Result<String> {
+        let answer = self.respond(request, result)?;
+        sink(Piece::Text(&answer));
+
+        Ok(answer)
+    }
+
+    /// This function returns the tone the last answer was delivered in, for those backends that
+    /// ask for answers as structured output, if it came back structured.
+    fn tone(&self) -> Option<Tone> {
+        None
+    }
+
+    /// This function returns the number of tokens the last answer took up, and its cost if known,
+    /// for those backends that go through an LLM.
+    fn usage(&self) -> Option<Usage> {
+        None
+    }
+}
+
+/// This function builds the backend selected in the settings the game was started with, wrapped
+/// as the settings call for, with the agent to fetch answers with and the quota of the API to hold
+/// requests back by for the backends that need them.
+pub(crate) fn from_settings(
+    settings: &Settings,
+    agent: Agent,
+    throttle: Throttle,
+) -> Box<dyn ResponseBackend> {
+    let candidates = settings.candidates;
+
+    match settings.backend {
+        Backend::Bank(ref bank) => Box::new(Banked::new(bank.clone())),
+        Backend::Command(ref command) => {
+            let primary = ExternalCommand::new(command);
+            if settings.structured {
+                with_fallback(Sanitized::new(Structured::new(primary)), candidates)
+            } else {
+                with_fallback(Sanitized::new(primary), candidates)
+            }
+        }
+        Backend::Mock => Box::new(Mock),
+        Backend::Offline => offline(),
+        Backend::OpenRouter(ref api_key) => {
+            let primary = OpenRouter::new(agent, api_key.as_ref(), &settings.base_url, throttle);
+            let fallbacks = settings.fallback_models.clone();
+            match (settings.structured, fallbacks.is_empty()) {
+                (false, true) => with_fallback(Sanitized::new(primary), candidates),
+                (false, false) => with_fallback(
+                    ModelChain::new(Sanitized::new(primary), fallbacks),
+                    candidates,
+                ),
+                (true, true) => with_fallback(Sanitized::new(Structured::new(primary)), candidates),
+                (true, false) => with_fallback(
+                    ModelChain::new(Sanitized::new(Structured::new(primary)), fallbacks),
+                    candidates,
+                ),
+            }
+        }
+    }
+}
+
+/// This function builds the offline backend, which answers with canned lines.
+pub(crate) fn offline() -> Box<dyn ResponseBackend> {
+    Box::new(Canned::new())
+}
+
+/// This function builds the backend the bank of answers is generated with, which is the one
+/// selected in the settings without the offline fallback, so that failures aren't mistaken for
+/// answers of the model.
+///
+/// The quota of the API is tracked apart from the one of the game, since the bank is built on its
+/// own. Only the OpenRouter API and the external command produce answers worth keeping in the bank;
+/// the other backends are turned down before getting here.
+pub(crate) fn for_bank(settings: &Settings, agent: Agent) -> Box<dyn ResponseBackend> {
+    match settings.backend {
+        Backend::Command(ref command) => Box::new(Sanitized::new(ExternalCommand::new(command))),
+        Backend::OpenRouter(ref api_key) => Box::new(Sanitized::new(OpenRouter::new(
+            agent,
+            api_key.as_ref(),
+            &settings.base_url,
+            Throttle::new(settings.account.as_ref().and_then(Account::free_quota)),
+        ))),
+        Backend::Bank(_) | Backend::Mock | Backend::Offline => Box::new(Canned::new()),
+    }
+}
+
+/// This function wraps the given backend with the offline fallback, asking it for the given number
+/// of candidate answers to pick the most novel one from if there's more than one.
+fn with_fallback<B>(primary: B, candidates: u8) -> Box<dyn ResponseBackend>
+where
+    B: ResponseBackend + 'static,
+{
+    if candidates > 1 {
+        Box::new(Fallback::new(Varied::new(primary, candidates)))
+    } else {
+        Box::new(Fallback::new(primary))
+    }
+}
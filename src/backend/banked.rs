@@ -0,0 +1,69 @@
+//! This module contains the backend answering with lines drawn from a bank of answers generated
+//! ahead of time, which doesn't need any network access either.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use fastrand::Rng;
+
+use crate::backend::canned::Canned;
+use crate::backend::deck::Deck;
+use crate::backend::ResponseBackend;
+use crate::bank::Bank;
+use crate::chat::Request;
+use crate::game::RandomResult;
+
+/// This structure represents a backend answering with a randomized line from the bank, for the
+/// persona narrating the game and the result of the game, and with the offline lines for those the
+/// bank has no lines for.
+pub(crate) struct Banked {
+    /// This field contains the bank the lines are drawn from.
+    bank: Bank,
+    /// This field contains the decks of lines drawn so far, by the identifier of the persona and
+    /// the result they answer.
+    decks: HashMap<(String, RandomResult), Deck>,
+    /// This field contains the note about how the last answer was produced.
+    notice: Option<String>,
+    /// This field contains the offline backend to answer with when the bank has no lines.
+    offline: Canned,
+    /// This field contains the random number generator used to shuffle the decks.
+    rng: Rng,
+}
+
+impl Banked {
+    /// This function creates a new backend drawing from the given bank.
+    pub(crate) fn new(bank: Bank) -> Self {
+        Self {
+            bank,
+            decks: HashMap::new(),
+            notice: None,
+            offline: Canned::new(),
+            rng: Rng::new(),
+        }
+    }
+}
+
+impl ResponseBackend for Banked {
+    fn notice(&self) -> Option<String> {
+        self.notice.clone()
+    }
+
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        let persona = request.persona();
+        let Some(lines) = self.bank.lines(persona.id(), result) else {
+            self.notice = Some(format!(
+                "offline, the bank has no lines for {}",
+                persona.name()
+            ));
+            return Ok(self.offline.line(persona, result));
+        };
+
+        self.notice = Some(format!("from the bank, by {}", self.bank.model()));
+        let deck = self
+            .decks
+            .entry((persona.id().to_owned(), result))
+            .or_insert_with(|| Deck::new(lines.to_vec()));
+
+        Ok(deck.draw(&mut self.rng).to_owned())
+    }
+}
@@ -0,0 +1,62 @@
+//! This module contains the offline backend, which answers with a bank of canned lines in the voice
+//! of the persona narrating the game.
+
+use anyhow::Result;
+use fastrand::Rng;
+
+use crate::backend::deck::Deck;
+use crate::backend::ResponseBackend;
+use crate::chat::Request;
+use crate::game::RandomResult;
+use crate::personas::Persona;
+
+/// This structure represents a backend that doesn't need any network access, as it answers with a
+/// randomized line from the bank of lines of the persona narrating the game, for each of the
+/// results of a game.
+pub(crate) struct Canned {
+    /// This field contains the deck of lines to answer correct guesses with.
+    correct: Deck,
+    /// This field contains the deck of lines to answer incorrect guesses with.
+    incorrect: Deck,
+    /// This field contains the identifier of the persona the decks hold the lines of, if any.
+    persona: Option<String>,
+    /// This field contains the random number generator used to shuffle the decks.
+    rng: Rng,
+}
+
+impl Canned {
+    /// This function draws the next line of the given persona for the given result of a game,
+    /// shuffling new decks whenever the persona changes.
+    pub(crate) fn line(&mut self, persona: &Persona, result: RandomResult) -> String {
+        if self.persona.as_deref() != Some(persona.id()) {
+            self.correct = Deck::new(persona.fallback(RandomResult::Correct).to_vec());
+            self.incorrect = Deck::new(persona.fallback(RandomResult::Incorrect).to_vec());
+            self.persona = Some(persona.id().to_owned());
+        }
+
+        match result {
+            RandomResult::Correct => self.correct.draw(&mut self.rng).to_owned(),
+            RandomResult::Incorrect => self.incorrect.draw(&mut self.rng).to_owned(),
+        }
+    }
+
+    /// This function creates a new offline backend, with its decks shuffled on the first draw.
+    pub(crate) fn new() -> Self {
+        Self {
+            correct: Deck::new(Vec::new()),
+            incorrect: Deck::new(Vec::new()),
+            persona: None,
+            rng: Rng::new(),
+        }
+    }
+}
+
+impl ResponseBackend for Canned {
+    fn notice(&self) -> Option<String> {
+        Some("offline".to_owned())
+    }
+
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        Ok(self.line(request.persona(), result))
+    }
+}
@@ -0,0 +1,146 @@
+//! This module contains the backend wrapper that tries a chain of models in order, for as long as
+//! the ones before fail in a way worth trying another model for.
+
+use core::cell::Cell;
+
+use anyhow::Result;
+
+use crate::backend::error::CompletionError;
+use crate::backend::{Piece, ResponseBackend};
+use crate::chat::{Request, Usage};
+use crate::game::RandomResult;
+use crate::mood::Tone;
+
+/// This structure wraps a backend so that whenever the model asked for fails to answer because it's
+/// rate limited or down, the same request is sent to each of the fallback models in order, until
+/// one of them answers.
+pub(crate) struct ModelChain<B> {
+    /// This field contains the models to fall back to, in order.
+    fallbacks: Vec<String>,
+    /// This field contains the model the last answer was produced by, if any.
+    model: Option<String>,
+    /// This field contains the note about which model produced the last answer.
+    notice: Option<String>,
+    /// This field contains the wrapped backend, asked with each model in turn.
+    primary: B,
+}
+
+impl<B> ModelChain<B>
+where
+    B: ResponseBackend,
+{
+    /// This function asks the wrapped backend for an answer through the given function, first with
+    /// the model in the request and then with each of the fallback models other than it, for as
+    /// long as they fail with an error worth trying another model for, and nothing of their answer
+    /// has been shown, as told by the other given function.
+    fn ask<F>(
+        &mut self,
+        request: &Request,
+        untouched: &dyn Fn() -> bool,
+        mut ask: F,
+    ) -> Result<String>
+    where
+        F: FnMut(&mut B, &Request) -> Result<String>,
+    {
+        self.model = None;
+        self.notice = None;
+
+        let mut model = request.model();
+        let mut failed = Vec::new();
+        let mut answer = ask(&mut self.primary, request);
+
+        for fallback in self
+            .fallbacks
+            .iter()
+            .filter(|&fallback| fallback != request.model())
+        {
+            match answer {
+                Err(ref err) if untouched() && is_retryable(err) => failed.push(model),
+                Ok(_) | Err(_) => break,
+            }
+            model = fallback;
+            answer = ask(&mut self.primary, &request.with_model(fallback));
+        }
+
+        let answer = answer?;
+        self.model = Some(model.to_owned());
+        self.notice = Some(if failed.is_empty() {
+            format!("answered by {model}")
+        } else {
+            format!("answered by {model}, as {} failed", failed.join(", "))
+        });
+
+        Ok(answer)
+    }
+
+    /// This function wraps the given backend so that the given models are fallen back to, in order.
+    pub(crate) const fn new(primary: B, fallbacks: Vec<String>) -> Self {
+        Self {
+            fallbacks,
+            model: None,
+            notice: None,
+            primary,
+        }
+    }
+}
+
+impl<B> ResponseBackend for ModelChain<B>
+where
+    B: ResponseBackend,
+{
+    fn model(&self) -> Option<String> {
+        self.model.clone()
+    }
+
+    fn notice(&self) -> Option<String> {
+        self.notice.clone()
+    }
+
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        self.ask(request, &|| true, |primary, request| {
+            primary.respond(request, result)
+        })
+    }
+
+    fn retain_fallbacks(&mut self, keep: &dyn Fn(&str) -> bool) -> bool {
+        let count = self.fallbacks.len();
+        self.fallbacks.retain(|fallback| keep(fallback));
+
+        self.fallbacks.len() < count
+    }
+
+    /// This function streams the answer, falling back to the next model only for as long as no
+    /// piece of the answer has been handed over to the sink, so that no piece is ever handed over
+    /// twice.
+    fn stream(
+        &mut self,
+        request: &Request,
+        result: RandomResult,
+        sink: &mut dyn FnMut(Piece<'_>),
+    ) -> Result<String> {
+        let streamed = Cell::new(false);
+
+        self.ask(request, &|| !streamed.get(), |primary, request| {
+            primary.stream(request, result, &mut |piece| {
+                streamed.set(true);
+                sink(piece);
+            })
+        })
+    }
+
+    fn tone(&self) -> Option<Tone> {
+        self.primary.tone()
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.primary.usage()
+    }
+}
+
+/// This function returns whether the given error is worth trying another model for, which is the
+/// case when the model is rate limited, held back by the quota of the API, down or fails to answer,
+/// but not when the request itself is at fault.
+fn is_retryable(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<CompletionError>()
+        .is_some_and(|err| err.is_retryable() || err.is_throttled())
+}
@@ -0,0 +1,74 @@
+//! This module contains the backend that delegates answering to an external command.
+
+use std::io::{ErrorKind, Write as _};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context as _, Result};
+
+use crate::backend::ResponseBackend;
+use crate::chat::Request;
+use crate::game::RandomResult;
+
+/// This structure represents a backend that runs a command through the shell for every result.
+///
+/// The command gets the chat completion request body as JSON through its standard input, and the
+/// result of the game through the `RANDY_RESULT` environment variable. Whatever it writes to its
+/// standard output is taken as the answer.
+pub(crate) struct ExternalCommand {
+    /// This field contains the command line to hand over to the shell.
+    command: String,
+}
+
+impl ExternalCommand {
+    /// This function creates a new backend out of the command line to run on every result.
+    pub(crate) fn new(command: &str) -> Self {
+        Self {
+            command: command.to_owned(),
+        }
+    }
+}
+
+impl ResponseBackend for ExternalCommand {
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("RANDY_RESULT", result.name())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("failed to run the backend command `{}`", self.command))?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("the backend command has no stdin")?;
+        // A command that doesn't read the request may well exit before it's all written, which is
+        // no reason not to take what it printed.
+        match serde_json::to_writer(&mut stdin, request)
+            .map_err(std::io::Error::from)
+            .and_then(|()| stdin.flush())
+        {
+            Err(err) if err.kind() != ErrorKind::BrokenPipe => return Err(err.into()),
+            Ok(()) | Err(_) => {}
+        }
+        drop(stdin);
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "the backend command `{}` exited with {}",
+                self.command,
+                output.status
+            );
+        }
+
+        let answer = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        if answer.is_empty() {
+            bail!("the backend command `{}` printed no answer", self.command);
+        }
+
+        Ok(answer)
+    }
+}
@@ -0,0 +1,46 @@
+//! This module contains the deck lines are drawn from by the backends answering with lines picked
+//! beforehand, rather than with lines made up on the spot.
+
+use fastrand::Rng;
+
+/// This structure holds one of the banks of lines in a shuffled order, so that every line is used
+/// once before any of them gets repeated.
+pub(crate) struct Deck {
+    /// This field contains the index of the line drawn last, if any.
+    last: Option<usize>,
+    /// This field contains the lines the deck draws from.
+    lines: Vec<String>,
+    /// This field contains the indices of the lines yet to be drawn in the current round of the
+    /// deck, the next one being the last in the vector.
+    order: Vec<usize>,
+}
+
+impl Deck {
+    /// This function draws the next line from the deck, shuffling a new round of it if it has run
+    /// out of lines. The first line of a new round is never the last line of the previous one.
+    pub(crate) fn draw(&mut self, rng: &mut Rng) -> &str {
+        if self.order.is_empty() {
+            self.order = (0..self.lines.len()).collect();
+            rng.shuffle(&mut self.order);
+
+            let end = self.order.len().saturating_sub(1);
+            if end > 0 && self.order.last().copied() == self.last {
+                self.order.swap(0, end);
+            }
+        }
+
+        self.last = self.order.pop();
+        self.last
+            .and_then(|index| self.lines.get(index))
+            .map_or("", String::as_str)
+    }
+
+    /// This function creates a new, empty deck to be shuffled on the first draw.
+    pub(crate) const fn new(lines: Vec<String>) -> Self {
+        Self {
+            last: None,
+            lines,
+            order: Vec::new(),
+        }
+    }
+}
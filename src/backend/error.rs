@@ -0,0 +1,166 @@
+//! This module contains the typed errors a chat completion request can fail with.
+
+use core::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use crate::secret::ApiKey;
+
+/// This enumeration holds the categories of failures of a chat completion request, so that each
+/// one of them can be reported with a clear message, and retried only when it makes sense to.
+#[derive(Debug)]
+pub(crate) enum CompletionError {
+    /// This variant is used when the API rejects the API key, or the key lacks permissions.
+    Auth(String),
+    /// This variant is used when the answer is no longer wanted, and the request is given up on.
+    Cancelled,
+    /// This variant is used when the model answers with an empty message, or with no message at
+    /// all.
+    Empty,
+    /// This variant is used when the body of the response can't be made sense of.
+    Malformed(String),
+    /// This variant is used when the provider behind the model fails to produce an answer.
+    Provider(String),
+    /// This variant is used when the API turns down the request for having sent too many of them,
+    /// with the time it asks to wait for before retrying, if it tells.
+    RateLimited(Option<Duration>),
+    /// This variant is used when the API turns down the request for any other reason, such as an
+    /// unknown model.
+    Rejected(String),
+    /// This variant is used when the request is held back rather than sent, since the quota of the
+    /// API would only let it through after waiting for too long, along with how long.
+    Throttled(Duration),
+    /// This variant is used when the API takes too long to answer.
+    Timeout,
+    /// This variant is used when the API can't be reached at all.
+    Transport(String),
+}
+
+impl CompletionError {
+    /// This function classifies a response with an unsuccessful status code, along with the
+    /// message in its body and its `Retry-After` header, if any.
+    pub(crate) fn from_status(status: u16, message: &str, retry_after: Option<&str>) -> Self {
+        let detail = format!("{status}: {message}");
+
+        match status {
+            401 | 403 => Self::Auth(detail),
+            429 => Self::RateLimited(
+                retry_after
+                    .and_then(|value| value.trim().parse().ok())
+                    .map(Duration::from_secs),
+            ),
+            500..=599 => Self::Provider(detail),
+            _ => Self::Rejected(detail),
+        }
+    }
+
+    /// This function returns whether the request is worth retrying after failing this way.
+    pub(crate) const fn is_retryable(&self) -> bool {
+        !matches!(
+            *self,
+            Self::Auth(_) | Self::Cancelled | Self::Rejected(_) | Self::Throttled(_)
+        )
+    }
+
+    /// This function returns whether the request was held back by the quota of the API, which
+    /// another model may not be subject to.
+    pub(crate) const fn is_throttled(&self) -> bool {
+        matches!(*self, Self::Throttled(_))
+    }
+
+    /// This function returns the same error with every occurrence of the given API key taken out of
+    /// its details, in case the API echoed it back.
+    pub(crate) fn redact(self, api_key: &ApiKey) -> Self {
+        match self {
+            Self::Auth(detail) => Self::Auth(api_key.redact(&detail)),
+            Self::Malformed(detail) => Self::Malformed(api_key.redact(&detail)),
+            Self::Provider(detail) => Self::Provider(api_key.redact(&detail)),
+            Self::Rejected(detail) => Self::Rejected(api_key.redact(&detail)),
+            Self::Transport(detail) => Self::Transport(api_key.redact(&detail)),
+            Self::Cancelled
+            | Self::Empty
+            | Self::RateLimited(_)
+            | Self::Throttled(_)
+            | Self::Timeout => self,
+        }
+    }
+
+    /// This function returns the time the API asked to wait for before retrying, if it did.
+    pub(crate) const fn retry_after(&self) -> Option<Duration> {
+        match *self {
+            Self::RateLimited(retry_after) => retry_after,
+            Self::Auth(_)
+            | Self::Cancelled
+            | Self::Empty
+            | Self::Malformed(_)
+            | Self::Provider(_)
+            | Self::Rejected(_)
+            | Self::Throttled(_)
+            | Self::Timeout
+            | Self::Transport(_) => None,
+        }
+    }
+}
+
+impl Display for CompletionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Auth(ref detail) => write!(
+                f,
+                "the API key was rejected ({detail}); check that it's a key for the API in use"
+            ),
+            Self::Cancelled => write!(f, "the request was cancelled"),
+            Self::Empty => write!(f, "the model answered with an empty message"),
+            Self::Malformed(ref detail) => {
+                write!(f, "the API answered with a malformed body ({detail})")
+            }
+            Self::Provider(ref detail) => {
+                write!(f, "the model provider failed to answer ({detail})")
+            }
+            Self::RateLimited(_) => write!(
+                f,
+                "the API is rate limiting requests; wait a bit or pick another model"
+            ),
+            Self::Rejected(ref detail) => write!(f, "the API rejected the request ({detail})"),
+            Self::Throttled(delay) => {
+                let minutes = delay.as_secs().div_ceil(60);
+                let (count, unit) = if minutes > 90 {
+                    (minutes.div_ceil(60), "hours")
+                } else {
+                    (minutes, "minutes")
+                };
+                write!(
+                    f,
+                    "the rate limit of the API would hold the request back for {count} more \
+                     {unit}; wait or pick another model"
+                )
+            }
+            Self::Timeout => write!(f, "the API took too long to answer"),
+            Self::Transport(ref detail) => write!(f, "the API couldn't be reached ({detail})"),
+        }
+    }
+}
+
+impl std::error::Error for CompletionError {}
+
+impl From<ureq::Error> for CompletionError {
+    fn from(err: ureq::Error) -> Self {
+        match err {
+            ureq::Error::Timeout(_) => Self::Timeout,
+            ureq::Error::Json(err) => Self::Malformed(err.to_string()),
+            ureq::Error::StatusCode(status) => Self::from_status(status, "", None),
+            err => Self::Transport(err.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CompletionError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Malformed(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for CompletionError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Transport(err.to_string())
+    }
+}
@@ -0,0 +1,202 @@
+//! This module contains the backend wrapper that falls back to offline answers on failure.
+
+use anyhow::Result;
+
+use crate::backend::canned::Canned;
+use crate::backend::{Piece, ResponseBackend};
+use crate::chat::{Request, Usage};
+use crate::game::RandomResult;
+use crate::mood::Tone;
+
+/// This structure wraps a backend so that whenever it fails to answer, the answer is taken from the
+/// offline bank of lines instead, and the game can go on without network access.
+pub(crate) struct Fallback<B> {
+    /// This field contains whether the last answer was taken from the offline bank of lines.
+    fell_back: bool,
+    /// This field contains the note about how the last answer was produced.
+    notice: Option<String>,
+    /// This field contains the offline backend to answer with when the wrapped one fails.
+    offline: Canned,
+    /// This field contains the wrapped backend, tried first for every answer.
+    primary: B,
+}
+
+impl<B> Fallback<B> {
+    /// This function wraps the given backend with an offline fallback.
+    pub(crate) fn new(primary: B) -> Self {
+        Self {
+            fell_back: false,
+            notice: None,
+            offline: Canned::new(),
+            primary,
+        }
+    }
+}
+
+impl<B> ResponseBackend for Fallback<B>
+where
+    B: ResponseBackend,
+{
+    fn is_generated(&self) -> bool {
+        !self.fell_back
+    }
+
+    /// This function returns the model the wrapped backend produced the last answer with, or
+    /// nothing if it was taken from the offline bank of lines instead.
+    fn model(&self) -> Option<String> {
+        if self.fell_back {
+            return None;
+        }
+
+        self.primary.model()
+    }
+
+    fn notice(&self) -> Option<String> {
+        self.notice.clone()
+    }
+
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        match self.primary.respond(request, result) {
+            Ok(answer) => {
+                self.fell_back = false;
+                self.notice = self.primary.notice();
+                Ok(answer)
+            }
+            Err(err) => {
+                self.fell_back = true;
+                self.notice = Some(format!("offline, the request failed: {err}"));
+                Ok(self.offline.line(request.persona(), result))
+            }
+        }
+    }
+
+    fn retain_fallbacks(&mut self, keep: &dyn Fn(&str) -> bool) -> bool {
+        self.primary.retain_fallbacks(keep)
+    }
+
+    fn shown(&mut self, persona: &str, answer: &str) {
+        self.primary.shown(persona, answer);
+    }
+
+    fn stream(
+        &mut self,
+        request: &Request,
+        result: RandomResult,
+        sink: &mut dyn FnMut(Piece<'_>),
+    ) -> Result<String> {
+        match self.primary.stream(request, result, sink) {
+            Ok(answer) => {
+                self.fell_back = false;
+                self.notice = self.primary.notice();
+                Ok(answer)
+            }
+            Err(err) => {
+                self.fell_back = true;
+                self.notice = Some(format!("offline, the request failed: {err}"));
+                Ok(self.offline.line(request.persona(), result))
+            }
+        }
+    }
+
+    fn tone(&self) -> Option<Tone> {
+        self.primary.tone()
+    }
+
+    /// This function returns the number of tokens the wrapped backend took up for the last answer,
+    /// or nothing if it was taken from the offline bank of lines instead.
+    fn usage(&self) -> Option<Usage> {
+        if self.fell_back {
+            return None;
+        }
+
+        self.primary.usage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+    use crate::chat::Transcript;
+    use crate::generation::Generation;
+    use crate::personas::Personas;
+
+    /// This structure represents a backend that answers with a model other than the one asked for,
+    /// or fails to answer after having taken up tokens anyway.
+    struct Stub {
+        /// This field contains whether the backend fails to answer.
+        fails: bool,
+    }
+
+    impl ResponseBackend for Stub {
+        fn model(&self) -> Option<String> {
+            Some("fake/other".to_owned())
+        }
+
+        fn respond(&mut self, _request: &Request, _result: RandomResult) -> Result<String> {
+            if self.fails {
+                return Err(anyhow!("the API is down"));
+            }
+
+            Ok("Howdy, partner.".to_owned())
+        }
+
+        fn usage(&self) -> Option<Usage> {
+            serde_json::from_str(r#"{"completion_tokens": 5, "prompt_tokens": 10}"#).ok()
+        }
+    }
+
+    /// This function asks a backend wrapped with the offline fallback for an answer, failing or
+    /// not as told, and returns the backend along with the answer.
+    fn answer(fails: bool) -> (Fallback<Stub>, String) {
+        let personas = Personas::load().expect("the built-in personas should load");
+        let request = Request::new(
+            &personas.get("cowboy"),
+            &Transcript::default(),
+            "Incorrect.",
+            "fake/model",
+            &Generation::default(),
+            false,
+        );
+        let mut backend = Fallback::new(Stub { fails });
+        let answer = backend
+            .respond(&request, RandomResult::Incorrect)
+            .expect("the fallback should always answer");
+
+        (backend, answer)
+    }
+
+    #[test]
+    fn answers_tell_how_they_were_produced() {
+        let (backend, answer) = answer(false);
+
+        assert_eq!(
+            answer, "Howdy, partner.",
+            "the answer should be the wrapped one"
+        );
+        assert!(backend.is_generated(), "the answer should be generated");
+        assert_eq!(
+            backend.model().as_deref(),
+            Some("fake/other"),
+            "the model should be the one that answered"
+        );
+        assert!(backend.usage().is_some(), "the usage should be kept");
+    }
+
+    #[test]
+    fn offline_answers_tell_no_model_or_usage() {
+        let (backend, answer) = answer(true);
+
+        assert!(!answer.is_empty(), "the answer should be an offline line");
+        assert!(!backend.is_generated(), "the answer shouldn't be generated");
+        assert!(backend.model().is_none(), "there should be no model");
+        assert!(backend.usage().is_none(), "there should be no usage");
+        assert!(
+            backend
+                .notice()
+                .is_some_and(|notice| notice.contains("the API is down")),
+            "the notice should tell why"
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! This module contains the mock backend, meant to try out the game without any network access.
+
+use anyhow::Result;
+
+use crate::backend::ResponseBackend;
+use crate::chat::Request;
+use crate::game::RandomResult;
+
+/// This structure represents a backend that answers with the very same message the LLM would
+/// otherwise have to answer to, so that it's easy to check what the game is sending.
+pub(crate) struct Mock;
+
+impl ResponseBackend for Mock {
+    fn respond(&mut self, request: &Request, _result: RandomResult) -> Result<String> {
+        Ok(request.prompt().to_owned())
+    }
+}
@@ -0,0 +1,242 @@
+//! This module contains the backend that fetches answers through the OpenRouter API.
+
+use std::io::{BufRead as _, BufReader};
+use std::time::Duration;
+
+use anyhow::Result;
+use ureq::http::Response as HttpResponse;
+use ureq::{Agent, Body};
+
+use crate::backend::error::CompletionError;
+use crate::backend::retry::RetryPolicy;
+use crate::backend::{Piece, ResponseBackend};
+use crate::chat::{ErrorResponse, Request, Response, StreamChunk, Usage};
+use crate::game::RandomResult;
+use crate::secret::ApiKey;
+use crate::throttle::Throttle;
+use crate::worker::doze;
+
+/// This structure represents a backend that issues chat completion requests to the OpenRouter API,
+/// or to any other OpenAI-compatible API such as the ones of local LLM servers.
+pub(crate) struct OpenRouter {
+    /// This field contains the agent used for all requests, shared with the rest of the network
+    /// worker so that connections get reused.
+    agent: Agent,
+    /// This field contains the API key sent along every request, if the API requires one.
+    api_key: Option<ApiKey>,
+    /// This field contains the policy failed requests are retried with.
+    retry: RetryPolicy,
+    /// This field contains the quota of the API, which requests are held back by until they would
+    /// go through.
+    throttle: Throttle,
+    /// This field contains the URL chat completion requests are posted to.
+    url: String,
+    /// This field contains the number of tokens the last answer took up, if the API told.
+    usage: Option<Usage>,
+}
+
+impl OpenRouter {
+    /// This function posts a chat completion request and reads its whole response, returning the
+    /// answer along with the number of tokens the request took up, if the API tells.
+    fn complete(&self, request: &Request) -> Result<(String, Option<Usage>), CompletionError> {
+        let response: Response = self.post(request)?.into_body().read_json()?;
+        let usage = response.usage();
+
+        response
+            .into_content()
+            .filter(|content| !content.trim().is_empty())
+            .map(|content| (content, usage))
+            .ok_or(CompletionError::Empty)
+    }
+
+    /// This function posts a streamed chat completion request and reads its server-sent events line
+    /// by line, handing over the piece of the message in each event to the sink. Lines other than
+    /// events, such as the comments the API sends to keep the connection alive, are skipped. The
+    /// number of tokens the request took up is returned, if the API tells. The response is given up
+    /// on as soon as the answer is no longer wanted.
+    fn complete_streamed(
+        &self,
+        request: &Request,
+        sink: &mut dyn FnMut(Piece<'_>),
+        answer: &mut String,
+    ) -> Result<Option<Usage>, CompletionError> {
+        let reader = BufReader::new(self.post(request)?.into_body().into_reader());
+        let mut usage = None;
+
+        for line in reader.lines() {
+            if request.is_cancelled() {
+                return Err(CompletionError::Cancelled);
+            }
+
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                break;
+            }
+
+            let chunk: StreamChunk = serde_json::from_str(data)?;
+            usage = chunk.usage().or(usage);
+            if let Some(piece) = chunk.into_content().map_err(CompletionError::Provider)? {
+                answer.push_str(&piece);
+                sink(Piece::Text(&piece));
+            }
+        }
+
+        if answer.trim().is_empty() {
+            return Err(CompletionError::Empty);
+        }
+
+        Ok(usage)
+    }
+
+    /// This function creates a new backend for the API at the given base URL, authenticated with
+    /// the given API key if there's one, and held back by the given quota. The agent must be
+    /// configured not to treat unsuccessful status codes as errors, so that their bodies and
+    /// headers can be inspected.
+    pub(crate) fn new(
+        agent: Agent,
+        api_key: Option<&ApiKey>,
+        base_url: &str,
+        throttle: Throttle,
+    ) -> Self {
+        Self {
+            agent,
+            api_key: api_key.cloned(),
+            retry: RetryPolicy::default(),
+            throttle,
+            url: format!("{base_url}/chat/completions"),
+            usage: None,
+        }
+    }
+
+    /// This function waits for the given time before retrying the given request after it failed
+    /// with the given error, letting the interface know that it's held back if the API turned it
+    /// down for having sent too many requests. The wait is cut short, failing, once the answer is
+    /// no longer wanted.
+    fn pause(
+        &self,
+        delay: Duration,
+        err: &CompletionError,
+        request: &Request,
+    ) -> Result<(), CompletionError> {
+        let waited = if matches!(*err, CompletionError::RateLimited(_)) {
+            self.throttle.pause(delay, request.cancellation())
+        } else {
+            doze(delay, request.cancellation())
+        };
+
+        if waited {
+            Ok(())
+        } else {
+            Err(CompletionError::Cancelled)
+        }
+    }
+
+    /// This function posts a chat completion request, with the authorization header only if there's
+    /// an API key to send, and classifies the response if it's unsuccessful, taking the API key out
+    /// of the error in case the API echoed it back.
+    fn post(&self, request: &Request) -> Result<HttpResponse<Body>, CompletionError> {
+        self.send(request).map_err(|err| match self.api_key {
+            Some(ref api_key) => err.redact(api_key),
+            None => err,
+        })
+    }
+
+    /// This function sends a chat completion request for [`Self::post`], once the quota of the API
+    /// lets it through, and takes in the rate limit headers of the response. The request isn't sent
+    /// at all if the answer is no longer wanted.
+    fn send(&self, request: &Request) -> Result<HttpResponse<Body>, CompletionError> {
+        if request.is_cancelled() {
+            return Err(CompletionError::Cancelled);
+        }
+
+        self.throttle
+            .admit(request.model(), request.cancellation())?;
+
+        let post = self.agent.post(&self.url);
+        let post = match self.api_key {
+            Some(ref api_key) => {
+                post.header("Authorization", format!("Bearer {}", api_key.expose()))
+            }
+            None => post,
+        };
+        let response = post.send_json(request)?;
+        self.throttle
+            .observe(response.status().as_u16(), response.headers());
+
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        let message = response
+            .into_body()
+            .read_json::<ErrorResponse>()
+            .map(ErrorResponse::into_message)
+            .unwrap_or_default();
+
+        Err(CompletionError::from_status(
+            status,
+            &message,
+            retry_after.as_deref(),
+        ))
+    }
+}
+
+impl ResponseBackend for OpenRouter {
+    fn respond(&mut self, request: &Request, _result: RandomResult) -> Result<String> {
+        self.usage = None;
+        let (answer, usage) = self.retry.run(
+            || self.complete(request),
+            |delay, err| self.pause(delay, err, request),
+        )?;
+        self.usage = usage;
+
+        Ok(answer)
+    }
+
+    /// This function streams the answer, retrying failed requests only for as long as no piece of
+    /// the answer has been handed over to the sink, so that no piece is ever handed over twice.
+    fn stream(
+        &mut self,
+        request: &Request,
+        result: RandomResult,
+        sink: &mut dyn FnMut(Piece<'_>),
+    ) -> Result<String> {
+        if !request.streamed() {
+            let answer = self.respond(request, result)?;
+            sink(Piece::Text(&answer));
+            return Ok(answer);
+        }
+
+        self.usage = None;
+        let mut retry = 0;
+        loop {
+            let mut answer = String::new();
+            match self.complete_streamed(request, sink, &mut answer) {
+                Ok(usage) => {
+                    self.usage = usage;
+                    break Ok(answer);
+                }
+                Err(err) => match self.retry.backoff(retry, &err) {
+                    Some(delay) if answer.is_empty() => {
+                        self.pause(delay, &err, request)?;
+                        retry += 1;
+                    }
+                    Some(_) | None => break Err(err.into()),
+                },
+            }
+        }
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+}
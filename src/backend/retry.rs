@@ -0,0 +1,65 @@
+//! This module contains the policy chat completion requests are retried with.
+
+use std::time::Duration;
+
+use crate::backend::error::CompletionError;
+
+/// This structure holds the bounds within which a failed request gets retried, waiting an
+/// exponentially longer time before each new attempt.
+pub(crate) struct RetryPolicy {
+    /// This field contains the maximum number of attempts, including the first one.
+    attempts: u32,
+    /// This field contains the time to wait for before the first retry, doubled on each one after.
+    base_delay: Duration,
+    /// This field contains the longest time to wait for before any retry, even when the API asks
+    /// for a longer one.
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// This function returns the time to wait for before the given retry, counting from zero, after
+    /// failing with the given error, or nothing if the error is not worth retrying or there are no
+    /// attempts left. The time the API asks for takes precedence over the exponential backoff.
+    pub(crate) fn backoff(&self, retry: u32, err: &CompletionError) -> Option<Duration> {
+        (err.is_retryable() && retry.saturating_add(1) < self.attempts).then(|| {
+            err.retry_after()
+                .unwrap_or_else(|| self.base_delay.saturating_mul(2_u32.saturating_pow(retry)))
+                .min(self.max_delay)
+        })
+    }
+
+    /// This function runs the given request until it succeeds, fails with an error that is not
+    /// worth retrying, or runs out of attempts, in which case the last error is returned. The wait
+    /// before every retry goes through the given function, along with the error that prompted it,
+    /// which may cut the wait short by failing itself.
+    pub(crate) fn run<T, F, P>(&self, mut request: F, pause: P) -> Result<T, CompletionError>
+    where
+        F: FnMut() -> Result<T, CompletionError>,
+        P: Fn(Duration, &CompletionError) -> Result<(), CompletionError>,
+    {
+        let mut retry = 0;
+
+        loop {
+            match request() {
+                Ok(value) => break Ok(value),
+                Err(err) => match self.backoff(retry, &err) {
+                    Some(delay) => {
+                        pause(delay, &err)?;
+                        retry += 1;
+                    }
+                    None => break Err(err),
+                },
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! This module contains the backend wrapper that cleans up the answers of another backend.
+
+use anyhow::{anyhow, Result};
+
+use crate::backend::{Piece, ResponseBackend};
+use crate::chat::{Request, Usage};
+use crate::game::RandomResult;
+use crate::mood::Tone;
+use crate::sanitize::{sanitize, StreamFilter};
+
+/// This constant holds the number of times an answer that is off-format once cleaned up gets
+/// requested again.
+const RETRIES: u32 = 1;
+
+/// This structure wraps a backend so that its answers are stripped of reasoning blocks, markdown
+/// and emoji, and kept short. Answers with nothing left after that are requested again, and if they
+/// still come back off-format, the request fails so that the backend wrapping this one can fall
+/// back on something else.
+pub(crate) struct Sanitized<B> {
+    /// This field contains the wrapped backend.
+    primary: B,
+    /// This field contains the number of tokens the wrapped backend took up for the last answer,
+    /// added up over every time it was asked.
+    usage: Option<Usage>,
+}
+
+impl<B> Sanitized<B>
+where
+    B: ResponseBackend,
+{
+    /// This function wraps the given backend so that its answers are cleaned up.
+    pub(crate) const fn new(primary: B) -> Self {
+        Self {
+            primary,
+            usage: None,
+        }
+    }
+
+    /// This function adds the number of tokens the wrapped backend took up the last time it was
+    /// asked to the ones for the answer so far.
+    fn tally(&mut self) {
+        if let Some(usage) = self.primary.usage() {
+            Usage::tally(&mut self.usage, usage);
+        }
+    }
+}
+
+impl<B> ResponseBackend for Sanitized<B>
+where
+    B: ResponseBackend,
+{
+    fn model(&self) -> Option<String> {
+        self.primary.model()
+    }
+
+    fn notice(&self) -> Option<String> {
+        self.primary.notice()
+    }
+
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        self.usage = None;
+
+        for _ in 0..=RETRIES {
+            let answer = self.primary.respond(request, result);
+            self.tally();
+            if let Some(answer) = sanitize(&answer?) {
+                return Ok(answer);
+            }
+        }
+
+        Err(anyhow!("the answer was off-format even after asking again"))
+    }
+
+    fn retain_fallbacks(&mut self, keep: &dyn Fn(&str) -> bool) -> bool {
+        self.primary.retain_fallbacks(keep)
+    }
+
+    fn stream(
+        &mut self,
+        request: &Request,
+        result: RandomResult,
+        sink: &mut dyn FnMut(Piece<'_>),
+    ) -> Result<String> {
+        self.usage = None;
+
+        let mut filter = StreamFilter::default();
+        let answer = self
+            .primary
+            .stream(request, result, &mut |piece| match piece {
+                Piece::Restart => {
+                    filter = StreamFilter::default();
+                    sink(Piece::Restart);
+                }
+                Piece::Text(text) => filter.push(text, sink),
+            });
+        self.tally();
+        if let Some(answer) = sanitize(&answer?) {
+            return Ok(answer);
+        }
+
+        for _ in 0..RETRIES {
+            let answer = self.primary.stream(request, result, &mut |_| {});
+            self.tally();
+            if let Some(answer) = sanitize(&answer?) {
+                return Ok(answer);
+            }
+        }
+
+        Err(anyhow!("the answer was off-format even after asking again"))
+    }
+
+    fn tone(&self) -> Option<Tone> {
+        self.primary.tone()
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+}
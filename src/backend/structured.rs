@@ -0,0 +1,116 @@
+//! This module contains the backend wrapper that asks for answers as structured output, along with
+//! the mood they are delivered in.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::backend::error::CompletionError;
+use crate::backend::{Piece, ResponseBackend};
+use crate::chat::{Request, Usage};
+use crate::game::RandomResult;
+use crate::mood::{self, Tone};
+
+/// This structure wraps a backend so that answers are asked for as structured output, with the line
+/// along with the mood it's delivered in and how strongly, and parsed into the line and its tone.
+///
+/// Answers that don't come back structured are taken as free text, without any tone. Models that
+/// turn down structured output altogether are remembered, and asked for free text from then on.
+pub(crate) struct Structured<B> {
+    /// This field contains the models that turned down structured output.
+    plain: HashSet<String>,
+    /// This field contains the wrapped backend.
+    primary: B,
+    /// This field contains the tone the last answer was delivered in, if it came back structured.
+    tone: Option<Tone>,
+}
+
+impl<B> Structured<B> {
+    /// This function wraps the given backend so that answers are asked for as structured output.
+    pub(crate) fn new(primary: B) -> Self {
+        Self {
+            plain: HashSet::new(),
+            primary,
+            tone: None,
+        }
+    }
+}
+
+impl<B> ResponseBackend for Structured<B>
+where
+    B: ResponseBackend,
+{
+    fn model(&self) -> Option<String> {
+        self.primary.model()
+    }
+
+    fn notice(&self) -> Option<String> {
+        self.primary.notice()
+    }
+
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        self.tone = None;
+        if self.plain.contains(request.model()) {
+            return self.primary.respond(&request.unstreamed(), result);
+        }
+
+        let answer = match self
+            .primary
+            .respond(&request.with_format(mood::response_format()), result)
+        {
+            Err(err) if is_rejected(&err) => {
+                let _ = self.plain.insert(request.model().to_owned());
+                return self.primary.respond(&request.unstreamed(), result);
+            }
+            answer => answer?,
+        };
+
+        Ok(match mood::parse(&answer) {
+            Some((line, tone)) => {
+                self.tone = Some(tone);
+                line
+            }
+            None => answer,
+        })
+    }
+
+    fn retain_fallbacks(&mut self, keep: &dyn Fn(&str) -> bool) -> bool {
+        self.primary.retain_fallbacks(keep)
+    }
+
+    /// This function streams the answer only if it's asked for as free text, since structured
+    /// output can't be made sense of until it's all in, in which case it's handed over at once.
+    fn stream(
+        &mut self,
+        request: &Request,
+        result: RandomResult,
+        sink: &mut dyn FnMut(Piece<'_>),
+    ) -> Result<String> {
+        if self.plain.contains(request.model()) {
+            self.tone = None;
+            return self.primary.stream(request, result, sink);
+        }
+
+        let answer = self.respond(request, result)?;
+        sink(Piece::Text(&answer));
+
+        Ok(answer)
+    }
+
+    fn tone(&self) -> Option<Tone> {
+        self.tone
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.primary.usage()
+    }
+}
+
+/// This function returns whether the given error means the request was turned down, which is taken
+/// as the model not supporting structured output.
+fn is_rejected(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<CompletionError>(),
+        Some(&CompletionError::Rejected(_))
+    )
+}
@@ -0,0 +1,121 @@
+//! This module contains the backend wrapper that asks for several candidate answers and keeps the
+//! one least like the answers shown recently.
+
+use anyhow::{anyhow, Result};
+
+use crate::backend::ResponseBackend;
+use crate::chat::{Request, Usage};
+use crate::game::RandomResult;
+use crate::history::History;
+use crate::mood::Tone;
+
+/// This structure wraps a backend so that it's asked for several answers every time, and only the
+/// one that is the most novel next to the answers recently shown in the voice of the same persona
+/// is kept. It's only recorded as shown once it's told to have been, since answers requested ahead
+/// of time may never be.
+///
+/// Since the answer can't be picked until every candidate is in, the answers aren't streamed.
+pub(crate) struct Varied<B> {
+    /// This field contains the number of candidate answers to ask for every time.
+    candidates: u8,
+    /// This field contains the answers shown recently for each persona.
+    history: History,
+    /// This field contains the model the kept answer was produced by, if told.
+    model: Option<String>,
+    /// This field contains the note about how the kept answer was produced.
+    notice: Option<String>,
+    /// This field contains the wrapped backend.
+    primary: B,
+    /// This field contains the tone the kept answer was delivered in, if told.
+    tone: Option<Tone>,
+    /// This field contains the number of tokens all the candidate answers took up together.
+    usage: Option<Usage>,
+}
+
+impl<B> Varied<B> {
+    /// This function wraps the given backend so that it's asked for the given number of candidate
+    /// answers every time.
+    pub(crate) fn new(primary: B, candidates: u8) -> Self {
+        Self {
+            candidates,
+            history: History::load(),
+            model: None,
+            notice: None,
+            primary,
+            tone: None,
+            usage: None,
+        }
+    }
+}
+
+impl<B> ResponseBackend for Varied<B>
+where
+    B: ResponseBackend,
+{
+    fn model(&self) -> Option<String> {
+        self.model.clone()
+    }
+
+    fn notice(&self) -> Option<String> {
+        self.notice.clone()
+    }
+
+    /// This function asks the wrapped backend for every candidate answer in turn, leaving out the
+    /// ones it fails to produce, and fails only if it fails to produce any of them.
+    fn respond(&mut self, request: &Request, result: RandomResult) -> Result<String> {
+        let request = request.unstreamed();
+        let persona = request.persona().id();
+        let mut best: Option<(f64, String)> = None;
+        let mut failure = None;
+
+        self.model = None;
+        self.notice = None;
+        self.tone = None;
+        self.usage = None;
+
+        for _ in 0..self.candidates {
+            let answer = self.primary.respond(&request, result);
+            if let Some(usage) = self.primary.usage() {
+                Usage::tally(&mut self.usage, usage);
+            }
+
+            match answer {
+                Ok(answer) => {
+                    let novelty = self.history.novelty(persona, &answer);
+                    if best.as_ref().is_none_or(|&(best, _)| novelty > best) {
+                        self.model = self.primary.model();
+                        self.notice = self.primary.notice();
+                        self.tone = self.primary.tone();
+                        best = Some((novelty, answer));
+                    }
+                }
+                Err(err) => failure = Some(err),
+            }
+        }
+
+        let Some((_, answer)) = best else {
+            return Err(failure.unwrap_or_else(|| anyhow!("no candidate answer was asked for")));
+        };
+
+        Ok(answer)
+    }
+
+    fn retain_fallbacks(&mut self, keep: &dyn Fn(&str) -> bool) -> bool {
+        self.primary.retain_fallbacks(keep)
+    }
+
+    fn shown(&mut self, persona: &str, answer: &str) {
+        self.history.record(persona, answer);
+        // The history is merely a way of telling novel answers apart, so failing to keep it is no
+        // reason to fail the game.
+        drop(self.history.save());
+    }
+
+    fn tone(&self) -> Option<Tone> {
+        self.tone
+    }
+
+    fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+}
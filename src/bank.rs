@@ -0,0 +1,201 @@
+//! This module holds the bank of answers generated ahead of time, so that the game can be played
+//! without waiting for nor paying for an LLM on every round.
+//!
+//! A bank is built by asking the configured model for a number of answers to each result of a
+//! round, in the voice of each persona, and is kept as a JSON file, by default at
+//! `$XDG_DATA_HOME/randy/bank.json`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::backend;
+use crate::chat::{Request, Transcript};
+use crate::game::RandomResult;
+use crate::paths;
+use crate::settings::{Backend, Settings, DEFAULT_MODEL};
+use crate::worker;
+
+/// This constant holds the number of times the model is asked for an answer for every answer that
+/// ends up in the bank at most, since answers it already gave are left out.
+const ATTEMPTS_PER_ANSWER: usize = 3;
+
+/// This structure holds the answers generated ahead of time for each persona.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Bank {
+    /// This field contains the model the answers were generated with.
+    model: String,
+    /// This field contains the answers to each result, by the identifier of the persona they are in
+    /// the voice of.
+    personas: BTreeMap<String, BankLines>,
+}
+
+impl Bank {
+    /// This function builds a bank with up to the given number of answers to each result in the
+    /// voice of each of the given personas, or of all of them if none is given, asking the model
+    /// in the settings through their backend. Progress is reported to the given function after
+    /// every answer.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if the backend in the settings doesn't go through a model,
+    /// if any of the personas doesn't exist, or if the model fails to answer.
+    pub fn build(
+        settings: &Settings,
+        count: usize,
+        personas: &[String],
+        progress: &mut dyn FnMut(&str),
+    ) -> Result<Self> {
+        if matches!(
+            settings.backend,
+            Backend::Bank(_) | Backend::Mock | Backend::Offline
+        ) {
+            bail!(
+                "building a bank needs either the OpenRouter API or an external command to \
+                answer, with an API key for the former"
+            );
+        }
+
+        let model = settings.model.as_deref().unwrap_or(DEFAULT_MODEL);
+        let mut source = backend::for_bank(settings, worker::agent());
+        let ids: Vec<String> = if personas.is_empty() {
+            settings.personas.ids().map(str::to_owned).collect()
+        } else {
+            personas.to_vec()
+        };
+        let mut bank = Self {
+            model: model.to_owned(),
+            personas: BTreeMap::new(),
+        };
+
+        for id in ids {
+            if !settings.personas.contains(&id) {
+                bail!("there's no persona called `{id}`");
+            }
+            let persona = settings.personas.get(&id);
+            let mut lines = BankLines::default();
+
+            for result in [RandomResult::Correct, RandomResult::Incorrect] {
+                let request = Request::new(
+                    &persona,
+                    &Transcript::default(),
+                    result.message(),
+                    model,
+                    &settings.generation,
+                    false,
+                );
+                let answers = lines.get_mut(result);
+
+                for _ in 0..count * ATTEMPTS_PER_ANSWER {
+                    if answers.len() >= count {
+                        break;
+                    }
+                    let answer = source.respond(&request, result)?;
+                    if !answers.contains(&answer) {
+                        answers.push(answer);
+                    }
+                    progress(&format!(
+                        "{id}, {}: {} of {count}",
+                        result.name(),
+                        answers.len()
+                    ));
+                }
+            }
+
+            drop(bank.personas.insert(id, lines));
+        }
+
+        Ok(bank)
+    }
+
+    /// This function returns the default location of the bank, which is `bank.json` in the data
+    /// directory of the game.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("bank.json"))
+    }
+
+    /// This function returns the answers to the given result in the voice of the persona with the
+    /// given identifier, if the bank has any.
+    pub(crate) fn lines(&self, persona: &str, result: RandomResult) -> Option<&[String]> {
+        self.personas
+            .get(persona)
+            .map(|lines| lines.get(result))
+            .filter(|lines| !lines.is_empty())
+    }
+
+    /// This function reads the bank at the given path.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if the file can't be read, or if it isn't a valid bank.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents =
+            fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// This function returns the model the answers in the bank were generated with.
+    #[must_use]
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// This function writes the bank to the given path, creating the directories leading to it if
+    /// needed.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if the directories or the file can't be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// This function returns the number of answers in the bank.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.personas
+            .values()
+            .map(|lines| lines.correct.len() + lines.incorrect.len())
+            .sum()
+    }
+}
+
+/// This structure holds the answers to each result in the voice of a persona.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct BankLines {
+    /// This field contains the answers to correct guesses.
+    correct: Vec<String>,
+    /// This field contains the answers to incorrect guesses.
+    incorrect: Vec<String>,
+}
+
+impl BankLines {
+    /// This function returns the answers to the given result.
+    fn get(&self, result: RandomResult) -> &[String] {
+        match result {
+            RandomResult::Correct => &self.correct,
+            RandomResult::Incorrect => &self.incorrect,
+        }
+    }
+
+    /// This function returns the answers to the given result, to add more of them.
+    const fn get_mut(&mut self, result: RandomResult) -> &mut Vec<String> {
+        match result {
+            RandomResult::Correct => &mut self.correct,
+            RandomResult::Incorrect => &mut self.incorrect,
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//! This module holds the catalog of models available through the API, shared by the validation of
+//! the command-line arguments and the model picker in the options menu.
+//!
+//! The catalog is fetched once and cached on disk, so that it's only fetched again once the cache
+//! goes stale, and even then only if it changed. Whenever the API can't be reached, a stale cache
+//! is still better than nothing.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+use ureq::Agent;
+
+use crate::paths;
+
+/// This constant holds the time after which a cached catalog is revalidated against the API.
+const TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// This structure holds the contents of the cache file of a catalog.
+#[derive(Serialize, Deserialize)]
+struct Cache {
+    /// This field contains the entity tag the API sent along the catalog, if any.
+    etag: Option<String>,
+    /// This field contains the time the catalog was last fetched or revalidated at, in seconds
+    /// since the Unix epoch.
+    fetched_at: u64,
+    /// This field contains the models in the catalog.
+    models: Vec<Model>,
+}
+
+/// This structure holds the modalities a model takes in and puts out. Any of them may be missing
+/// for APIs other than the OpenRouter one.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Architecture {
+    /// This field contains the kinds of content the model takes in, e.g. text or image.
+    input_modalities: Vec<String>,
+    /// This field contains the kinds of content the model puts out.
+    output_modalities: Vec<String>,
+}
+
+/// This enumeration holds the filters the model picker can narrow the catalog down with.
+#[derive(Clone, Copy, Default)]
+pub(crate) enum Filter {
+    /// This variant lets every model through.
+    #[default]
+    All,
+    /// This variant only lets through the models that cost nothing to use.
+    Free,
+    /// This variant only lets through the models that put out text.
+    TextOutput,
+}
+
+impl Filter {
+    /// This function returns a short description of the filter to show to the user.
+    pub(crate) const fn label(self) -> &'static str {
+        match self {
+            Self::All => "all models",
+            Self::Free => "free only",
+            Self::TextOutput => "text output only",
+        }
+    }
+
+    /// This function returns whether the filter lets the given model through.
+    pub(crate) fn matches(self, model: &Model) -> bool {
+        match self {
+            Self::All => true,
+            Self::Free => model.is_free(),
+            Self::TextOutput => model.outputs_text(),
+        }
+    }
+
+    /// This function returns the filter that comes after this one, going back to the first one
+    /// after the last one.
+    pub(crate) const fn next(self) -> Self {
+        match self {
+            Self::All => Self::Free,
+            Self::Free => Self::TextOutput,
+            Self::TextOutput => Self::All,
+        }
+    }
+}
+
+/// This structure holds the single item in the response to the model list request to the API.
+/// Every field but the identifier is optional, since APIs other than the OpenRouter one tend to
+/// leave them out.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Model {
+    /// This field contains the modalities the model takes in and puts out.
+    #[serde(default)]
+    architecture: Architecture,
+    /// This field contains the maximum number of tokens the model can take in at once.
+    context_length: Option<u64>,
+    /// This field contains the description of the model given by the provider.
+    description: Option<String>,
+    /// This field contains the unique identifier name of a model.
+    id: String,
+    /// This field contains the public brand name of the model.
+    name: Option<String>,
+    /// This field contains the price of using the model.
+    pricing: Option<Pricing>,
+}
+
+impl Model {
+    /// This function returns the maximum number of tokens the model can take in at once, if known.
+    pub(crate) const fn context_length(&self) -> Option<u64> {
+        self.context_length
+    }
+
+    /// This function returns the description of the model, if there's any.
+    pub(crate) fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// This function returns the unique identifier name of the model.
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// This function returns whether the model costs nothing to use, which is the case for the
+    /// models with a zero price for both prompt and completion tokens, or with the `:free` suffix.
+    pub(crate) fn is_free(&self) -> bool {
+        self.id.ends_with(":free")
+            || self.prices().is_some_and(|(prompt, completion)| {
+                prompt.abs() < f64::EPSILON && completion.abs() < f64::EPSILON
+            })
+    }
+
+    /// This function returns the modalities the model takes in and puts out, in that order.
+    pub(crate) fn modalities(&self) -> (&[String], &[String]) {
+        (
+            &self.architecture.input_modalities,
+            &self.architecture.output_modalities,
+        )
+    }
+
+    /// This function returns the public brand name of the model, if known.
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// This function returns whether the model puts out text. Models that don't tell are assumed
+    /// to do so.
+    pub(crate) fn outputs_text(&self) -> bool {
+        self.architecture.output_modalities.is_empty()
+            || self
+                .architecture
+                .output_modalities
+                .iter()
+                .any(|modality| modality == "text")
+    }
+
+    /// This function returns the prices of prompt and completion tokens, in dollars per million
+    /// tokens, if known. Negative prices mean that the price varies with the model picked behind
+    /// the scenes.
+    pub(crate) fn prices(&self) -> Option<(f64, f64)> {
+        let pricing = self.pricing.as_ref()?;
+        let prompt = pricing.prompt.parse::<f64>().ok()?;
+        let completion = pricing.completion.parse::<f64>().ok()?;
+
+        Some((prompt * 1_000_000.0, completion * 1_000_000.0))
+    }
+}
+
+/// This structure holds the response to the model list request to the API.
+#[derive(Deserialize)]
+struct ModelList {
+    /// This field contains the vector containing information about all models usable through the
+    /// API.
+    data: Vec<Model>,
+}
+
+/// This structure holds the price of using a model, in dollars per token, as the API lists them.
+#[derive(Serialize, Deserialize)]
+struct Pricing {
+    /// This field contains the price of every token put out by the model.
+    completion: String,
+    /// This field contains the price of every token taken in by the model.
+    prompt: String,
+}
+
+/// This structure holds the catalog of models available through an API.
+pub struct ModelCatalog {
+    /// This field contains the models in the catalog.
+    models: Vec<Model>,
+    /// This field contains whether the catalog comes from a stale cache because the API couldn't
+    /// be reached.
+    stale: bool,
+}
+
+impl ModelCatalog {
+    /// This function returns whether the catalog holds a model with the given identifier.
+    #[must_use]
+    pub fn contains(&self, id: &str) -> bool {
+        self.models.iter().any(|model| model.id == id)
+    }
+
+    /// This function fetches the catalog of the API at the given base URL through the given agent,
+    /// preferring a fresh cache over the network, and a stale cache over an error.
+    pub(crate) fn fetch(agent: &Agent, base_url: &str) -> Result<Self> {
+        let path = cache_path(base_url);
+        let cache = path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|contents| serde_json::from_slice::<Cache>(&contents).ok());
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let cache = match cache {
+            Some(cache) if now.saturating_sub(cache.fetched_at) < TTL.as_secs() => cache,
+            cache => match revalidate(agent, base_url, cache, now) {
+                Ok(cache) => {
+                    if let Some(ref path) = path {
+                        // The cache is only an optimization, so failing to write it is no reason
+                        // to fail fetching the catalog.
+                        drop(write_cache(path, &cache));
+                    }
+                    cache
+                }
+                Err((Some(cache), _)) => {
+                    return Ok(Self {
+                        models: cache.models,
+                        stale: true,
+                    })
+                }
+                Err((None, err)) => return Err(err),
+            },
+        };
+
+        Ok(Self {
+            models: cache.models,
+            stale: false,
+        })
+    }
+
+    /// This function returns the models in the catalog that the given filter lets through, in the
+    /// order the API lists them in.
+    pub(crate) fn filtered(&self, filter: Filter) -> Vec<&Model> {
+        self.models
+            .iter()
+            .filter(|model| filter.matches(model))
+            .collect()
+    }
+
+    /// This function returns the model with the given identifier, if it's in the catalog.
+    pub(crate) fn get(&self, id: &str) -> Option<&Model> {
+        self.models.iter().find(|model| model.id == id)
+    }
+
+    /// This function returns the identifiers of the models in the catalog, in the order the API
+    /// lists them in.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.models.iter().map(|model| model.id.as_str())
+    }
+
+    /// This function returns whether the catalog comes from a stale cache because the API couldn't
+    /// be reached.
+    #[must_use]
+    pub const fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// This function fetches the catalog of the API at the given base URL, as [`Self::fetch`]
+    /// does, with an agent of its own.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if there's no cache to fall back to and the API either
+    /// can't be reached or answers with something other than a model list.
+    pub fn load(base_url: &str) -> Result<Self> {
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build()
+            .new_agent();
+
+        Self::fetch(&agent, base_url)
+    }
+
+    /// This function returns up to the given number of identifiers in the catalog that look the
+    /// most like the given one, for when it isn't in the catalog. Identifiers containing the given
+    /// one, or starting the same way, rank first, and the rest by their edit distance to it, as
+    /// long as it's small enough for them to plausibly be what was meant.
+    #[must_use]
+    pub fn suggestions(&self, id: &str, count: usize) -> Vec<&str> {
+        let wanted = id.to_lowercase();
+        let threshold = (wanted.chars().count() / 3).max(2);
+        let mut ranked: Vec<_> = self
+            .ids()
+            .filter_map(|candidate| {
+                let lowered = candidate.to_lowercase();
+                let prefix = wanted
+                    .chars()
+                    .zip(lowered.chars())
+                    .take_while(|&(left, right)| left == right)
+                    .count();
+                let distance = edit_distance(&wanted, &lowered);
+
+                if lowered.contains(&wanted) || wanted.contains(&lowered) {
+                    Some((0, distance, candidate))
+                } else if distance <= threshold || prefix * 3 >= wanted.chars().count().max(4) * 2 {
+                    Some((1, distance, candidate))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        ranked.sort_by_key(|&(tier, distance, _)| (tier, distance));
+
+        ranked
+            .into_iter()
+            .take(count)
+            .map(|(_, _, candidate)| candidate)
+            .collect()
+    }
+}
+
+/// This function returns the path of the cache file for the catalog of the API at the given base
+/// URL, so that each API gets a cache of its own.
+fn cache_path(base_url: &str) -> Option<PathBuf> {
+    let name: String = base_url
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect();
+
+    paths::cache_dir().map(|dir| dir.join(format!("models-{name}.json")))
+}
+
+/// This function returns the Levenshtein distance between two strings, i.e. the number of
+/// characters to insert, delete or replace to turn one into the other.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+
+    for (row, left_char) in left.chars().enumerate() {
+        let mut current = vec![row + 1];
+        for (column, &right_char) in right.iter().enumerate() {
+            let replace = previous.get(column).copied().unwrap_or_default()
+                + usize::from(left_char != right_char);
+            let delete = previous.get(column + 1).copied().unwrap_or_default() + 1;
+            let insert = current.get(column).copied().unwrap_or_default() + 1;
+            current.push(replace.min(delete).min(insert));
+        }
+        previous = current;
+    }
+
+    previous.last().copied().unwrap_or_default()
+}
+
+/// This function asks the API for the catalog, sending along the entity tag of the cache if
+/// there's one, so that the API only sends the catalog back if it changed. On failure, the cache is
+/// handed back along with the error.
+fn revalidate(
+    agent: &Agent,
+    base_url: &str,
+    cache: Option<Cache>,
+    now: u64,
+) -> Result<Cache, (Option<Cache>, anyhow::Error)> {
+    let request = agent.get(format!("{base_url}/models"));
+    let request = match cache.as_ref().and_then(|cache| cache.etag.as_deref()) {
+        Some(etag) => request.header("If-None-Match", etag),
+        None => request,
+    };
+
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(err) => return Err((cache, err.into())),
+    };
+
+    if response.status() == 304 {
+        if let Some(mut cache) = cache {
+            cache.fetched_at = now;
+            return Ok(cache);
+        }
+    }
+
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err((
+            cache,
+            anyhow::anyhow!("the API answered the model list request with {status}"),
+        ));
+    }
+
+    let etag = response
+        .headers()
+        .get("etag")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+
+    match response.into_body().read_json::<ModelList>() {
+        Ok(list) => Ok(Cache {
+            etag,
+            fetched_at: now,
+            models: list.data,
+        }),
+        Err(err) => Err((cache, err.into())),
+    }
+}
+
+/// This function writes the cache file of a catalog, creating its directory if needed.
+fn write_cache(path: &Path, cache: &Cache) -> Result<()> {
+    let Some(dir) = path.parent() else {
+        bail!("the cache path {} has no parent", path.display());
+    };
+
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    fs::write(path, serde_json::to_vec(cache)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,462 @@
+//! The chat module contains the types serialized and deserialized in a chat completion request to
+//! the OpenRouter API.
+//!
+//! These are shared between the backends that need to know about the exchange, be it to send it
+//! over the network or to hand it over to some other program.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::generation::Generation;
+use crate::personas::Persona;
+
+/// This constant holds the rules of the game for the system prompt of the chat completion request
+/// of the OpenRouter API, which come before the description of the voice of the persona narrating
+/// the game.
+const RULES: &str = "You will be notified whether a user got a number right in a number guessing \
+game or not through messages starting with \"Correct\" or \"Incorrect,\" respectively, followed \
+by the details of the round. Your task is to, depending on whether you were notified they got it \
+right, or not, to return an answer to the user in character, which may refer to the details of the \
+round. Make it a short text. Include just your answer and nothing more. Don't include emoji or \
+otherwise non-verbal content. The messages about the earlier rounds of the session, along with \
+your answers to them, may come before the one to answer to, so you can bring up streaks, comebacks \
+or repeated guesses, without repeating your earlier answers. After a round, the user may also talk \
+back to you, in which case you answer them in character, just as briefly.";
+
+/// This constant holds the number of earlier rounds the transcript of a session keeps at most.
+const TRANSCRIPT_ROUNDS: usize = 10;
+
+/// This constant holds the number of characters the transcript of a session keeps at most, so that
+/// long answers don't blow up the size of the requests.
+const TRANSCRIPT_CHARS: usize = 4000;
+
+/// This structure holds information about the messages to send to the LLM in a chat completion
+/// request to the OpenRouter API.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Messages {
+    /// This field contains information about the content of the specific message in question.
+    content: String,
+    /// This field contains information about who is it that is supposed to be reporting the
+    /// [`message`] field.
+    role: Role,
+}
+
+impl Messages {
+    /// This function creates a new message based on a given role for the chat exchange and the
+    /// contents of the message in question.
+    fn new(role: Role, content: &str) -> Self {
+        Self {
+            content: content.to_owned(),
+            role,
+        }
+    }
+}
+
+/// This structure is the main way of serializing information about the data we are interested in
+/// for the chat completion request to the OpenRouter API.
+#[derive(Clone, Serialize)]
+pub(crate) struct Request {
+    /// This field contains the flag raised once the answer is no longer wanted, shared with the
+    /// network worker, so that the backends can give up on it as soon as they can.
+    #[serde(skip)]
+    cancelled: Arc<AtomicBool>,
+    /// This field contains the parameters the LLM generates its answer with, along with the
+    /// preferences for the providers the request is routed to.
+    #[serde(flatten)]
+    generation: Generation,
+    /// This field contains information about the sequence of messages to initially issue to the
+    /// LLM.
+    messages: Vec<Messages>,
+    /// This field contains information about the model to be used in the request.
+    model: String,
+    /// This field contains the persona narrating the game, for the backends answering offline.
+    #[serde(skip)]
+    persona: Arc<Persona>,
+    /// This field contains the format the answer is asked for in, if it's asked for as structured
+    /// output rather than as free text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<Value>,
+    /// This field contains whether the response should be streamed back as server-sent events.
+    #[serde(skip_serializing_if = "core::ops::Not::not")]
+    stream: bool,
+    /// This field contains the options of a streamed response, which ask for the usage of the
+    /// request to be sent along the last event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+impl Request {
+    /// This function has the request share the given flag, raised once the answer is no longer
+    /// wanted.
+    pub(crate) fn cancel_with(&mut self, cancelled: Arc<AtomicBool>) {
+        self.cancelled = cancelled;
+    }
+
+    /// This function returns the flag raised once the answer is no longer wanted.
+    pub(crate) fn cancellation(&self) -> &AtomicBool {
+        &self.cancelled
+    }
+
+    /// This function returns whether the answer is no longer wanted.
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// This function returns the model to be used in the request.
+    pub(crate) fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// This function creates a new chat completion request body solely with the information
+    /// required by the program, i.e. the system prompt in the voice of the given persona, the
+    /// transcript of the earlier rounds of the session and the message telling the LLM about the
+    /// result of the round, to be answered by the given model with the given parameters.
+    pub(crate) fn new(
+        persona: &Arc<Persona>,
+        transcript: &Transcript,
+        message: &str,
+        model: &str,
+        generation: &Generation,
+        stream: bool,
+    ) -> Self {
+        let mut system = format!("{RULES}\n\n{}", persona.system().trim());
+
+        if !persona.examples().is_empty() {
+            system.push_str("\n\nHere are a few examples of answers in your voice:\n");
+            for example in persona.examples() {
+                system.push_str("\n- ");
+                system.push_str(example);
+            }
+        }
+
+        let mut messages = vec![Messages::new(Role::System, &system)];
+        messages.extend(transcript.messages.iter().cloned());
+        messages.push(Messages::new(Role::User, message));
+
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            generation: generation.clone(),
+            model: model.to_owned(),
+            messages,
+            persona: Arc::clone(persona),
+            response_format: None,
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+        }
+    }
+
+    /// This function returns the persona narrating the game.
+    pub(crate) fn persona(&self) -> &Persona {
+        &self.persona
+    }
+
+    /// This function returns the contents of the last message in the request, which is the one
+    /// the LLM is expected to answer to.
+    pub(crate) fn prompt(&self) -> &str {
+        self.messages
+            .last()
+            .map_or("", |message| message.content.as_str())
+    }
+
+    /// This function returns whether the response to the request is meant to be streamed.
+    pub(crate) const fn streamed(&self) -> bool {
+        self.stream
+    }
+
+    /// This function returns the same request, to be answered as a whole rather than streamed.
+    pub(crate) fn unstreamed(&self) -> Self {
+        let mut request = self.clone();
+        request.stream = false;
+        request.stream_options = None;
+
+        request
+    }
+
+    /// This function returns the same request, to be answered as a whole in the given format, as
+    /// structured output can't be made sense of until it's all in.
+    pub(crate) fn with_format(&self, format: Value) -> Self {
+        let mut request = self.unstreamed();
+        request.response_format = Some(format);
+
+        request
+    }
+
+    /// This function returns the same request, to be answered by the given model instead.
+    pub(crate) fn with_model(&self, model: &str) -> Self {
+        let mut request = self.clone();
+        model.clone_into(&mut request.model);
+
+        request
+    }
+}
+
+/// This structure holds the rolling transcript of the earlier rounds of a session, as the messages
+/// telling the LLM about them along with the answers to them, so that the LLM can refer back to
+/// them. Only the latest rounds are kept, as many as fit in the bounds of the transcript.
+#[derive(Default)]
+pub(crate) struct Transcript {
+    /// This field contains the messages of the earlier rounds, the oldest one first.
+    messages: VecDeque<Messages>,
+}
+
+impl Transcript {
+    /// This function records a round in the transcript, as the message telling the LLM about it
+    /// and the answer to it, dropping the oldest rounds that no longer fit.
+    pub(crate) fn record(&mut self, message: &str, answer: &str) {
+        self.messages.push_back(Messages::new(Role::User, message));
+        self.messages
+            .push_back(Messages::new(Role::Assistant, answer));
+
+        while self.messages.len() > TRANSCRIPT_ROUNDS * 2
+            || self
+                .messages
+                .iter()
+                .map(|message| message.content.len())
+                .sum::<usize>()
+                > TRANSCRIPT_CHARS
+        {
+            drop(self.messages.pop_front());
+            drop(self.messages.pop_front());
+        }
+    }
+}
+
+/// This structure represents the response of a chat completion request to the OpenRouter API only
+/// with the values that the program needs.
+#[derive(Deserialize)]
+pub(crate) struct Response {
+    /// This field contains the vector of messages that the LLM has produced.
+    choices: Vec<ResponseMessages>,
+    /// This field contains the number of tokens the request took up, if the API tells.
+    usage: Option<Usage>,
+}
+
+impl Response {
+    /// This function consumes the response and returns the contents of the last message produced
+    /// by the LLM, if there's any.
+    pub(crate) fn into_content(self) -> Option<String> {
+        self.choices
+            .into_iter()
+            .last()
+            .map(|choice| choice.message.content)
+    }
+
+    /// This function returns the number of tokens the request took up, if the API tells.
+    pub(crate) const fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+}
+
+/// This structure holds information about the one-level indented message containing the responses
+/// from the LLM.
+#[derive(Deserialize)]
+struct ResponseMessages {
+    /// This field contains the actual responses from the LLM.
+    message: Messages,
+}
+
+/// This structure represents one of the server-sent events in which a streamed chat completion
+/// response comes in, only with the values that the program needs.
+#[derive(Deserialize)]
+pub(crate) struct StreamChunk {
+    /// This field contains the pieces of the messages that the LLM has produced so far.
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    /// This field contains the error that cut the stream short, if any.
+    error: Option<ApiError>,
+    /// This field contains the number of tokens the request took up, which only the last event
+    /// holds, if the API tells.
+    usage: Option<Usage>,
+}
+
+impl StreamChunk {
+    /// This function consumes the chunk and returns either the piece of the last message it holds,
+    /// or the message of the error that interrupted the stream.
+    pub(crate) fn into_content(self) -> Result<Option<String>, String> {
+        match self.error {
+            Some(error) => Err(error.message),
+            None => Ok(self
+                .choices
+                .into_iter()
+                .last()
+                .and_then(|choice| choice.delta.content)),
+        }
+    }
+
+    /// This function returns the number of tokens the request took up, if the chunk holds it.
+    pub(crate) const fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+}
+
+/// This structure holds the options of a streamed response.
+#[derive(Clone, Serialize)]
+struct StreamOptions {
+    /// This field contains whether the usage of the request is sent along the last event.
+    include_usage: bool,
+}
+
+/// This structure holds the one-level indented piece of a message in a streamed response.
+#[derive(Deserialize)]
+struct StreamChoice {
+    /// This field contains the actual piece of the message.
+    delta: StreamDelta,
+}
+
+/// This structure holds the contents of a piece of a message in a streamed response.
+#[derive(Deserialize)]
+struct StreamDelta {
+    /// This field contains the text of the piece, which may be missing in some of them.
+    content: Option<String>,
+}
+
+/// This structure holds the description of an error sent by the API, be it in the body of an
+/// unsuccessful response or in the middle of a streamed one.
+#[derive(Deserialize)]
+struct ApiError {
+    /// This field contains the description of the error.
+    message: String,
+}
+
+/// This structure represents the body of an unsuccessful response from the API.
+#[derive(Deserialize)]
+pub(crate) struct ErrorResponse {
+    /// This field contains the error the request failed with.
+    error: ApiError,
+}
+
+impl ErrorResponse {
+    /// This function consumes the body and returns the description of the error.
+    pub(crate) fn into_message(self) -> String {
+        self.error.message
+    }
+}
+
+/// This enumeration represents the role in a chat exchange between a user and the LLM.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Role {
+    /// This variant represents the role of the LLM.
+    Assistant,
+    /// This variant represents the role of the system prompt.
+    System,
+    /// This variant represents the role of the user.
+    User,
+}
+
+/// This structure holds the number of tokens a chat completion request took up, along with its
+/// cost in dollars, if known.
+#[derive(Clone, Copy, Deserialize)]
+pub(crate) struct Usage {
+    /// This field contains the number of tokens in the answer.
+    #[serde(default)]
+    completion_tokens: u64,
+    /// This field contains the cost of the request in dollars, which only some APIs tell, and which
+    /// is otherwise estimated from the prices of the model.
+    #[serde(default)]
+    cost: Option<f64>,
+    /// This field contains the number of tokens in the request.
+    #[serde(default)]
+    prompt_tokens: u64,
+}
+
+impl Usage {
+    /// This function adds up the usage of another request to this one. The cost is only known if
+    /// it's known for both of them.
+    pub(crate) fn add(&mut self, other: Self) {
+        self.completion_tokens += other.completion_tokens;
+        self.prompt_tokens += other.prompt_tokens;
+        self.cost = self.cost.zip(other.cost).map(|(cost, other)| cost + other);
+    }
+
+    /// This function returns the number of tokens in the answer.
+    pub(crate) const fn completion_tokens(&self) -> u64 {
+        self.completion_tokens
+    }
+
+    /// This function returns the cost of the request in dollars, if known.
+    pub(crate) const fn cost(&self) -> Option<f64> {
+        self.cost
+    }
+
+    /// This function estimates the cost of the request from the given prices of prompt and
+    /// completion tokens, in dollars per million tokens, unless it's already known.
+    pub(crate) fn price(&mut self, (prompt, completion): (f64, f64)) {
+        if self.cost.is_none() {
+            #[expect(
+                clippy::cast_precision_loss,
+                reason = "token counts are nowhere near the point of losing precision"
+            )]
+            let cost = (self.prompt_tokens as f64)
+                .mul_add(prompt, self.completion_tokens as f64 * completion)
+                / 1_000_000.0;
+            self.cost = Some(cost);
+        }
+    }
+
+    /// This function returns the number of tokens in the request.
+    pub(crate) const fn prompt_tokens(&self) -> u64 {
+        self.prompt_tokens
+    }
+
+    /// This function adds up the usage of another request to the given total, or starts the total
+    /// off with it if there's none yet, so that its cost is kept.
+    pub(crate) fn tally(total: &mut Option<Self>, other: Self) {
+        match *total {
+            Some(ref mut total) => total.add(other),
+            None => *total = Some(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// This function returns the usage of a request with the given numbers of tokens and cost.
+    const fn usage(prompt_tokens: u64, completion_tokens: u64, cost: Option<f64>) -> Usage {
+        Usage {
+            completion_tokens,
+            cost,
+            prompt_tokens,
+        }
+    }
+
+    #[test]
+    fn known_costs_are_summed() {
+        let mut total = None;
+        Usage::tally(&mut total, usage(100, 10, Some(0.25)));
+        Usage::tally(&mut total, usage(200, 20, Some(0.5)));
+
+        let total = total.expect("the total should have been started off");
+        assert_eq!(
+            (total.prompt_tokens(), total.completion_tokens()),
+            (300, 30),
+            "the tokens of both requests should be summed"
+        );
+        assert!(
+            total
+                .cost()
+                .is_some_and(|cost| (cost - 0.75).abs() < f64::EPSILON),
+            "the costs told by the API should be summed"
+        );
+    }
+
+    #[test]
+    fn unknown_costs_stay_unknown() {
+        let mut total = None;
+        Usage::tally(&mut total, usage(100, 10, Some(0.25)));
+        Usage::tally(&mut total, usage(200, 20, None));
+
+        assert!(
+            total.is_some_and(|total| total.cost().is_none()),
+            "the cost should be unknown if it's unknown for any request"
+        );
+    }
+}
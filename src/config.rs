@@ -0,0 +1,90 @@
+//! This module holds the configuration file, read from the configuration directory of the game.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+
+use crate::generation::Generation;
+use crate::paths;
+use crate::template::Prompts;
+
+/// This structure holds the settings that can be given through the configuration file.
+///
+/// Every one of them is optional, and those given through either one of the command-line or the
+/// environment take precedence over the ones in the file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// This field contains the command to run through the shell for the API key, whose output's
+    /// first line is the key.
+    pub api_key_cmd: Option<String>,
+    /// This field contains the file whose first line is the API key.
+    pub api_key_file: Option<PathBuf>,
+    /// This field contains the location of the bank of answers generated ahead of time.
+    pub bank: Option<PathBuf>,
+    /// This field contains the base URL of the OpenAI-compatible API to fetch answers from.
+    pub base_url: Option<String>,
+    /// This field contains the number of candidate answers to ask for every round.
+    pub candidates: Option<u8>,
+    /// This field contains the models to fall back to, in order, when the one asked for fails.
+    pub fallback_models: Vec<String>,
+    /// This field contains the parameters the LLM generates its answers with, given through the
+    /// `[generation]` table, along with the preferences for the providers requests are routed to,
+    /// given through the `[generation.provider]` table.
+    pub generation: Generation,
+    /// This field contains the model to use for the answers.
+    pub model: Option<String>,
+    /// This field contains the identifier of the persona narrating the game.
+    pub persona: Option<String>,
+    /// This field contains whether answers to both results of a round are requested ahead of time.
+    pub prefetch: Option<bool>,
+    /// This field contains the templates of the message telling the LLM about every round, given
+    /// through the `[prompts]` table.
+    pub prompts: Prompts,
+    /// This field contains the most the answers of a session may cost, in dollars.
+    pub spend_cap: Option<f64>,
+    /// This field contains whether answers are asked for as structured output, along with a mood.
+    pub structured: Option<bool>,
+}
+
+impl Config {
+    /// This function reads the configuration file at the given path, or at the default location
+    /// if no path is given. A missing file at the default location makes for an empty
+    /// configuration.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if the file can't be read, or if it isn't valid TOML with
+    /// only the known settings in it.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_owned(), true),
+            None => match Self::path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound && !required => {
+                return Ok(Self::default());
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+
+        toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// This function returns the default location of the configuration file, which is
+    /// `config.toml` in the configuration directory of the game.
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        paths::config_dir().map(|dir| dir.join("config.toml"))
+    }
+}
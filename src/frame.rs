@@ -1,14 +1,22 @@
 //! This module holds experimental attempts at a TUI for randy.
 
+pub(crate) mod account;
+pub(crate) mod chat;
+pub(crate) mod input;
 pub(crate) mod main_menu;
 pub(crate) mod options;
+pub(crate) mod params_prompt;
+pub(crate) mod persona_prompt;
 pub(crate) mod prompt;
 pub(crate) mod random_prompt;
 pub(crate) mod repeat_prompt;
+pub(crate) mod response;
 
 use anyhow::Result;
 use console::{style, Key, Term};
 
+use crate::frame::input::read_key;
+
 /// This trait implements methods for menus with selectable items.
 pub(crate) trait Selected
 where
@@ -71,13 +79,25 @@ where
     Ok(())
 }
 
+/// This function draws a dim status line on the last row of the screen, right under a menu, without
+/// moving on to a new line so that the screen doesn't scroll.
+pub(crate) fn draw_status(term: &Term, status: &str) -> Result<()> {
+    let cols = term.size().1 as usize;
+    let status = console::truncate_str(status, cols.saturating_sub(2), "...");
+    let output = format!("{}", style(status).dim());
+    let output = console::pad_str(&output, cols, console::Alignment::Center, None);
+    term.write_str(&output)?;
+
+    Ok(())
+}
+
 /// This function reads in a key and redraws a menu to select the option corresponding with the
 /// arrow key movement.
 pub(crate) fn nav_menu<T>(term: &Term, menu: &mut T) -> Result<T::Action>
 where
     T: Selected,
 {
-    let input = term.read_key()?;
+    let input = read_key()?;
 
     if input == Key::Enter {
         return Ok(menu.action());
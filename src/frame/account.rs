@@ -0,0 +1,83 @@
+//! This module contains experimental support for rendering the status of the account behind the
+//! API key.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use console::{pad_str, style, Key, Term};
+
+use crate::account::Account;
+use crate::frame::input::{poll_key, read_key};
+use crate::frame::response::draw_waiting;
+use crate::game::TICK;
+use crate::worker::{Event, Job, Worker};
+
+/// This function draws a frame with the given lines about the account centered on the screen,
+/// under a title, along with a hint on how to go back.
+fn draw_account(term: &Term, lines: &[String]) -> Result<()> {
+    let (rows, cols) = term.size();
+    let fill = (rows as usize / 2).saturating_sub(lines.len() / 2 + 2);
+
+    term.clear_screen()?;
+
+    for _ in 1..fill {
+        term.write_line("")?;
+    }
+
+    let output = format!("{}", style("Account").bold());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+    term.write_line("")?;
+
+    for line in lines {
+        let line = console::truncate_str(line, (cols as usize).saturating_sub(4), "...");
+        let output = pad_str(&line, cols as usize, console::Alignment::Center, None);
+        term.write_line(&output)?;
+    }
+
+    term.write_line("")?;
+
+    let output = format!("{}", style("Press any key to go back").dim());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+
+    Ok(())
+}
+
+/// This function has the network worker fetch the status of the account behind the API key again,
+/// animating the frame while waiting, and shows it until a key is pressed. The status is returned
+/// to be kept for the status bar, if it was fetched. Pressing Esc while waiting goes back right
+/// away, and a failure is shown in place of the status rather than torn down the interface.
+pub(crate) fn nav_account(term: &Term, worker: &mut Worker) -> Result<Option<Account>> {
+    let ticket = worker.submit(Job::Account)?;
+    let mut tick = 0;
+
+    let (lines, account) = loop {
+        draw_waiting(term, "Checking the account", tick)?;
+        tick += 1;
+
+        if let Some(Event::Account(fetched)) = worker.poll(ticket, TICK)? {
+            break match fetched {
+                Ok(Some(fetched)) => (fetched.details(), Some(fetched)),
+                Ok(None) => (
+                    vec!["There's no account to tell about with this API or backend".to_owned()],
+                    None,
+                ),
+                Err(err) => (
+                    vec![format!("The account couldn't be checked: {err}")],
+                    None,
+                ),
+            };
+        }
+
+        if poll_key(Duration::ZERO)? == Some(Key::Escape) {
+            worker.cancel(ticket);
+            return Ok(None);
+        }
+    };
+
+    draw_account(term, &lines)?;
+    drop(read_key()?);
+
+    Ok(account)
+}
@@ -0,0 +1,69 @@
+//! This module contains experimental support for rendering a conversation between the player and
+//! the persona narrating the game, along with the reply the player is typing in.
+
+use anyhow::Result;
+use console::{pad_str, style, Term};
+
+use crate::frame::response::wrap;
+
+/// This enumeration holds who said each of the lines in a conversation.
+#[derive(Clone, Copy)]
+pub(crate) enum Speaker {
+    /// This variant is used for the lines of the persona narrating the game.
+    Narrator,
+    /// This variant is used for the lines of the player.
+    Player,
+}
+
+/// This function draws a frame with the latest lines of a conversation, as many as fit on the
+/// screen, followed by the reply the player is typing in and a status line below it.
+pub(crate) fn draw_chat(
+    term: &Term,
+    narrator: &str,
+    lines: &[(Speaker, String)],
+    input: Option<&str>,
+    status: &str,
+) -> Result<()> {
+    let (rows, cols) = term.size();
+    let mut wrapped = Vec::new();
+
+    for &(speaker, ref line) in lines {
+        let text = match speaker {
+            Speaker::Narrator => format!("{narrator}: {line}"),
+            Speaker::Player => format!("You: {line}"),
+        };
+        for part in wrap(&text, cols as usize) {
+            wrapped.push(match speaker {
+                Speaker::Narrator => format!("{}", style(part).bold()),
+                Speaker::Player => format!("{}", style(part).cyan()),
+            });
+        }
+        wrapped.push(String::new());
+    }
+
+    let room = (rows as usize).saturating_sub(4);
+    let skipped = wrapped.len().saturating_sub(room);
+
+    term.clear_screen()?;
+
+    for _ in wrapped.len().saturating_sub(skipped)..room {
+        term.write_line("")?;
+    }
+
+    for line in wrapped.iter().skip(skipped) {
+        term.write_line(&format!("  {line}"))?;
+    }
+
+    let prompt = match input {
+        Some(input) => format!("{}", style(format!("> {input}_")).bold().on_cyan()),
+        None => String::new(),
+    };
+    let prompt = pad_str(&prompt, cols as usize, console::Alignment::Center, None);
+    term.write_line(&prompt)?;
+
+    let status = format!("{}", style(status).dim());
+    let status = pad_str(&status, cols as usize, console::Alignment::Center, None);
+    term.write_line(&status)?;
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! This module holds the reader of keys from the terminal, which lets frames wait for a key for a
+//! limited time so that they can keep on animating in the meantime.
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use console::{Key, Term};
+
+/// This static variable holds the reader of keys shared by all frames. It is made static because
+/// there's a single terminal to read keys from, and the reader must be the only one doing so.
+static READER: LazyLock<Mutex<KeyReader>> = LazyLock::new(|| Mutex::new(KeyReader::spawn()));
+
+/// This structure holds the channels to a thread that reads keys from the terminal on demand.
+///
+/// The thread only reads a key when asked to, so that the terminal is never left waiting for a key
+/// that no frame is interested in. A frame that stops waiting for a key leaves the request pending,
+/// and the next frame to ask for a key gets the one read for the pending request.
+struct KeyReader {
+    /// This field contains the channel the keys read by the thread are received through.
+    keys: Receiver<std::io::Result<Key>>,
+    /// This field contains whether the thread has been asked for a key that hasn't been received
+    /// yet.
+    pending: bool,
+    /// This field contains the channel the thread is asked for keys through.
+    requests: Sender<()>,
+}
+
+impl KeyReader {
+    /// This function waits for a key for up to the given time, asking the thread for one first if
+    /// it's not reading one already.
+    fn poll(&mut self, timeout: Option<Duration>) -> Result<Option<Key>> {
+        if !self.pending {
+            self.requests
+                .send(())
+                .map_err(|err| anyhow!("the key reader has stopped: {err}"))?;
+            self.pending = true;
+        }
+
+        let key = match timeout {
+            Some(timeout) => match self.keys.recv_timeout(timeout) {
+                Ok(key) => key,
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow!("the key reader has stopped"));
+                }
+            },
+            None => self
+                .keys
+                .recv()
+                .map_err(|err| anyhow!("the key reader has stopped: {err}"))?,
+        };
+        self.pending = false;
+
+        Ok(Some(key?))
+    }
+
+    /// This function spawns the thread that reads keys from the terminal.
+    fn spawn() -> Self {
+        let (requests, requested) = mpsc::channel::<()>();
+        let (sender, keys) = mpsc::channel();
+
+        drop(thread::spawn(move || {
+            let term = Term::stdout();
+
+            while requested.recv().is_ok() {
+                if sender.send(term.read_key()).is_err() {
+                    break;
+                }
+            }
+        }));
+
+        Self {
+            keys,
+            pending: false,
+            requests,
+        }
+    }
+}
+
+/// This function waits for up to the given time for a key to be pressed, returning nothing if none
+/// was pressed in time.
+pub(crate) fn poll_key(timeout: Duration) -> Result<Option<Key>> {
+    READER
+        .lock()
+        .map_err(|err| anyhow!("the key reader is poisoned: {err}"))?
+        .poll(Some(timeout))
+}
+
+/// This function waits for a key to be pressed, for as long as it takes.
+pub(crate) fn read_key() -> Result<Key> {
+    READER
+        .lock()
+        .map_err(|err| anyhow!("the key reader is poisoned: {err}"))?
+        .poll(None)?
+        .ok_or_else(|| anyhow!("the key reader has stopped"))
+}
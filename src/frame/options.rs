@@ -7,8 +7,16 @@ use crate::frame::Selected;
 /// This enum holds information about whether one of its variants is currently selected in the menu
 #[derive(PartialEq)]
 pub(crate) enum OptionsMenu {
+    /// This variant is used to represent the "account" item in the options menu.
+    Account,
+    /// This variant is used to represent the "generation" item in the options menu.
+    Generation,
     /// This variant is used to represent the "model" item in the options menu.
     Model,
+    /// This variant is used to represent the "persona" item in the options menu.
+    Persona,
+    /// This variant is used to represent the "providers" item in the options menu.
+    Providers,
     /// This variant is used to represent the option to return back to the frame before the options
     /// menu.
     Return,
@@ -19,34 +27,73 @@ impl Selected for OptionsMenu {
 
     fn action(&self) -> Self::Action {
         match *self {
+            Self::Account => OptionsMenuAction::ShowAccount,
+            Self::Generation => OptionsMenuAction::ChangeGeneration,
             Self::Model => OptionsMenuAction::ChangeModel,
+            Self::Persona => OptionsMenuAction::ChangePersona,
+            Self::Providers => OptionsMenuAction::ChangeProviders,
             Self::Return => OptionsMenuAction::GoBack,
         }
     }
 
     /// This function returns all the enum variants as a vector.
     fn list(&self) -> Vec<Self> {
-        vec![Self::Model, Self::Return]
+        vec![
+            Self::Model,
+            Self::Persona,
+            Self::Generation,
+            Self::Providers,
+            Self::Account,
+            Self::Return,
+        ]
     }
 
     /// This function returns the next item in the menu after pressing one of the down arrow or the
     /// up arrow keys.
     fn next(&mut self, key: Key) {
         match *self {
-            Self::Model if key == Key::ArrowUp => {
-                *self = Self::Return;
+            Self::Model => {
+                if key == Key::ArrowUp {
+                    *self = Self::Return;
+                } else if key == Key::ArrowDown {
+                    *self = Self::Persona;
+                }
             }
-            Self::Model if key == Key::ArrowDown => {
-                *self = Self::Return;
+            Self::Persona => {
+                if key == Key::ArrowUp {
+                    *self = Self::Model;
+                } else if key == Key::ArrowDown {
+                    *self = Self::Generation;
+                }
             }
-            Self::Return if key == Key::ArrowUp => {
-                *self = Self::Model;
+            Self::Generation => {
+                if key == Key::ArrowUp {
+                    *self = Self::Persona;
+                } else if key == Key::ArrowDown {
+                    *self = Self::Providers;
+                }
             }
-            Self::Return if key == Key::ArrowUp => {
-                *self = Self::Model;
+            Self::Providers => {
+                if key == Key::ArrowUp {
+                    *self = Self::Generation;
+                } else if key == Key::ArrowDown {
+                    *self = Self::Account;
+                }
+            }
+            Self::Account => {
+                if key == Key::ArrowUp {
+                    *self = Self::Providers;
+                } else if key == Key::ArrowDown {
+                    *self = Self::Return;
+                }
+            }
+            Self::Return => {
+                if key == Key::ArrowUp {
+                    *self = Self::Account;
+                } else if key == Key::ArrowDown {
+                    *self = Self::Model;
+                }
             }
-            Self::Return => {}
-            Self::Model => {}
         }
     }
 
@@ -57,7 +104,11 @@ impl Selected for OptionsMenu {
     /// This function returns a string representation of the implicit object.
     fn repr(&self) -> &str {
         match *self {
+            Self::Account => "Account",
+            Self::Generation => "Generation",
             Self::Model => "Model",
+            Self::Persona => "Persona",
+            Self::Providers => "Providers",
             Self::Return => "Return",
         }
     }
@@ -67,11 +118,20 @@ impl Selected for OptionsMenu {
 /// the menu.
 #[derive(PartialEq)]
 pub(crate) enum OptionsMenuAction {
+    /// This variant is used when the user wants to change the generation parameters.
+    ChangeGeneration,
     /// This variant is used when the user wants to change the model in use.
     ChangeModel,
+    /// This variant is used when the user wants to change the persona narrating the game.
+    ChangePersona,
+    /// This variant is used when the user wants to change the preferences for providers.
+    ChangeProviders,
     /// This variant is used when the user decides to go back from the options menu to the previous
     /// frame.
     GoBack,
     /// This variant is used when the user presses a keybinding that does not trigger any action.
     Pass,
+    /// This variant is used when the user wants to see the status of the account behind the API
+    /// key.
+    ShowAccount,
 }
@@ -0,0 +1,148 @@
+//! This module contains experimental support for rendering a prompt to change the parameters the
+//! LLM generates its answers with, or the preferences for the providers requests are routed to.
+
+use anyhow::Result;
+use console::{pad_str, style, Key, Term};
+
+use crate::frame::input::read_key;
+use crate::generation::{Field, Generation};
+
+/// This structure holds the state of the prompt, i.e. the highlighted parameter, the value being
+/// typed in for it if it's being changed, and the reason the last value typed in was turned down.
+struct ParamsPrompt<'fields> {
+    /// This field contains the index of the highlighted parameter.
+    cursor: usize,
+    /// This field contains the reason the last value typed in was turned down, if it was.
+    error: Option<String>,
+    /// This field contains the parameters listed in the prompt.
+    fields: &'fields [Field],
+    /// This field contains the value being typed in for the highlighted parameter, if it's being
+    /// changed.
+    input: Option<String>,
+}
+
+/// This function draws a frame with the parameters and their values, the highlighted one standing
+/// out along with a hint at the values it takes, and the value being typed in if there's one.
+fn draw_params_prompt(
+    term: &Term,
+    title: &str,
+    prompt: &ParamsPrompt<'_>,
+    generation: &Generation,
+) -> Result<()> {
+    let (rows, cols) = term.size();
+    let fill = (rows as usize / 2).saturating_sub(prompt.fields.len() / 2 + 2);
+
+    term.clear_screen()?;
+
+    for _ in 1..fill {
+        term.write_line("")?;
+    }
+
+    let output = format!("{}", style(title).bold());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+    term.write_line("")?;
+
+    for (index, &field) in prompt.fields.iter().enumerate() {
+        let value = match prompt.input {
+            Some(ref input) if index == prompt.cursor => format!("{input}_"),
+            _ => Some(generation.get(field))
+                .filter(|value| !value.is_empty())
+                .unwrap_or_else(|| "default".to_owned()),
+        };
+        let item = format!("{}: {value}", field.label());
+        let item = console::truncate_str(&item, (cols as usize).saturating_sub(4), "...");
+        let output = if index == prompt.cursor {
+            format!("{}", style(item).bold().on_cyan())
+        } else {
+            format!("{}", style(item).bold())
+        };
+        let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+        term.write_line(&output)?;
+    }
+
+    term.write_line("")?;
+
+    if let Some(&field) = prompt.fields.get(prompt.cursor) {
+        let output = format!("{}", style(field.hint()).dim());
+        let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+        term.write_line(&output)?;
+    }
+
+    if let Some(ref error) = prompt.error {
+        let output = format!("{}", style(error).red());
+        let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+        term.write_line(&output)?;
+    }
+
+    let keys = if prompt.input.is_some() {
+        "Enter saves, leave it empty for the default; Esc cancels"
+    } else {
+        "Enter changes the value, Esc goes back"
+    };
+    let output = format!("{}", style(keys).dim());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+
+    Ok(())
+}
+
+/// This function lets the user change the given parameters one at a time, typing in their values,
+/// which are checked before they replace the current ones.
+pub(crate) fn nav_params_prompt(
+    term: &Term,
+    title: &str,
+    fields: &[Field],
+    generation: &mut Generation,
+) -> Result<()> {
+    let mut prompt = ParamsPrompt {
+        cursor: 0,
+        error: None,
+        fields,
+        input: None,
+    };
+
+    loop {
+        draw_params_prompt(term, title, &prompt, generation)?;
+
+        let key = read_key()?;
+        let Some(ref mut input) = prompt.input else {
+            match key {
+                Key::ArrowUp => {
+                    prompt.cursor = prompt.cursor.checked_sub(1).unwrap_or(fields.len() - 1);
+                }
+                Key::ArrowDown => prompt.cursor = (prompt.cursor + 1) % fields.len(),
+                Key::Enter => {
+                    if let Some(&field) = fields.get(prompt.cursor) {
+                        prompt.input = Some(generation.get(field));
+                    }
+                }
+                Key::Escape => break,
+                _ => {}
+            }
+            continue;
+        };
+
+        match key {
+            Key::Enter => {
+                if let Some(&field) = fields.get(prompt.cursor) {
+                    prompt.error = generation.set(field, input).err();
+                }
+                if prompt.error.is_none() {
+                    prompt.input = None;
+                }
+            }
+            Key::Escape => {
+                prompt.error = None;
+                prompt.input = None;
+            }
+            Key::Backspace => {
+                let _ = input.pop();
+            }
+            Key::Char(ch) if !ch.is_control() => input.push(ch),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! This module contains experimental support for rendering a prompt to pick the persona narrating
+//! the game.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use console::{pad_str, style, Key, Term};
+
+use crate::frame::input::read_key;
+use crate::personas::{Persona, Personas};
+
+/// This function draws a frame with the list of personas, the highlighted one standing out along
+/// with one of its example lines, and the current one marked as such.
+fn draw_persona_prompt(
+    term: &Term,
+    personas: &[&Arc<Persona>],
+    cursor: usize,
+    current: &Persona,
+) -> Result<()> {
+    let (rows, cols) = term.size();
+    let fill = (rows as usize / 2).saturating_sub(personas.len() / 2 + 2);
+
+    term.clear_screen()?;
+
+    for _ in 1..fill {
+        term.write_line("")?;
+    }
+
+    let output = format!("{}", style("Select the persona narrating the game").bold());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+    term.write_line("")?;
+
+    for (index, persona) in personas.iter().enumerate() {
+        let marker = if persona.id() == current.id() {
+            " (current)"
+        } else {
+            ""
+        };
+        let item = format!("{}{marker}", persona.name());
+        let output = if index == cursor {
+            format!("{}", style(item).bold().on_cyan())
+        } else {
+            format!("{}", style(item).bold())
+        };
+        let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+        term.write_line(&output)?;
+    }
+
+    term.write_line("")?;
+
+    if let Some(example) = personas
+        .get(cursor)
+        .and_then(|persona| persona.examples().first())
+    {
+        let example = console::truncate_str(example, (cols as usize).saturating_sub(4), "...");
+        let output = format!("{}", style(format!("\"{example}\"")).dim());
+        let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+        term.write_line(&output)?;
+    }
+
+    let output = format!("{}", style("Enter picks, Esc goes back").dim());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+
+    Ok(())
+}
+
+/// This function lets the user pick the persona narrating the game out of all the ones available,
+/// replacing the current one on Enter, or leaving it as is on Esc.
+pub(crate) fn nav_persona_prompt(
+    term: &Term,
+    personas: &Personas,
+    persona: &mut Arc<Persona>,
+) -> Result<()> {
+    let list: Vec<_> = personas.list().collect();
+    let mut cursor = list
+        .iter()
+        .position(|other| other.id() == persona.id())
+        .unwrap_or(0);
+
+    loop {
+        draw_persona_prompt(term, &list, cursor, persona)?;
+
+        match read_key()? {
+            Key::ArrowUp => cursor = cursor.checked_sub(1).unwrap_or(list.len() - 1),
+            Key::ArrowDown => cursor = (cursor + 1) % list.len(),
+            Key::Enter => {
+                if let Some(picked) = list.get(cursor) {
+                    *persona = Arc::clone(picked);
+                }
+                break;
+            }
+            Key::Escape => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
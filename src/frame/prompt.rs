@@ -1,189 +1,326 @@
 //! This module enables experimental support for basic prompts on fixed frames.
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::Result;
 use console::{style, Key, Term};
-use serde::Deserialize;
-
-/// This struct holds the single item in the response to the model list request to the OpenRouter
-/// API.
-#[derive(Deserialize)]
-struct Data {
-    /// This field contains the unique identifier name of a model.
-    id: String,
-}
 
-/// This struct holds the response to the model list request to the OpenRouter API.
-#[derive(Deserialize)]
-struct Response {
-    /// This field contains the vector containing information about all models usable through the
-    /// API.
-    data: Vec<Data>,
+use crate::catalog::{Filter, Model, ModelCatalog};
+use crate::frame::input::{poll_key, read_key};
+use crate::frame::response::{draw_hint, draw_response, draw_waiting};
+use crate::game::TICK;
+use crate::picks::Picks;
+use crate::worker::{Event, Job, Worker};
+
+/// This structure holds the state of the model picker: the models matching the search query and
+/// the filter, sorted with the favorites and recently used models on top, and the one highlighted.
+struct ModelPicker<'catalog> {
+    /// This field contains the catalog the models are picked from.
+    catalog: &'catalog ModelCatalog,
+    /// This field contains the position of the highlighted model among the matching ones.
+    cursor: usize,
+    /// This field contains the filter the catalog is narrowed down with.
+    filter: Filter,
+    /// This field contains the models matching both the search query and the filter, in the order
+    /// they are listed in.
+    matches: Vec<&'catalog Model>,
+    /// This field contains the models pinned as favorites and the ones used recently.
+    picks: Picks,
+    /// This field contains the search query typed in by the user.
+    query: String,
 }
 
-/// This enum contains the variants for which a prompt may have one element of it or the other
-/// selected.
-#[expect(
-    clippy::arbitrary_source_item_ordering,
-    reason = "It's best if the items are kept in the same order as they would appear in the actual menu prompt."
-)]
-#[derive(Clone)]
-enum SelectedItem {
-    /// This variant represents that the prompt itself is the one currently selected.
-    Selector,
-    /// This variant represents the capacity to go back to the previous frame.
-    Return,
+impl<'catalog> ModelPicker<'catalog> {
+    /// This function moves the highlight by the given number of models, stopping at either end of
+    /// the list.
+    fn move_cursor(&mut self, offset: isize) {
+        let last = self.matches.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(offset).min(last);
+    }
+
+    /// This function creates a new model picker over the given catalog, with the given model
+    /// highlighted if it's in the catalog.
+    fn new(catalog: &'catalog ModelCatalog, current: &str) -> Self {
+        let mut picker = Self {
+            catalog,
+            cursor: 0,
+            filter: Filter::default(),
+            matches: Vec::new(),
+            picks: Picks::load(),
+            query: String::new(),
+        };
+        picker.refresh(Some(current));
+        picker
+    }
+
+    /// This function works out the models matching the search query and the filter again, and
+    /// highlights the given model if it's still among them, or the best match otherwise.
+    fn refresh(&mut self, highlight: Option<&str>) {
+        let mut matches: Vec<_> = self
+            .catalog
+            .filtered(self.filter)
+            .into_iter()
+            .filter_map(|model| {
+                let score = fuzzy_score(&self.query, model.id()).into_iter();
+                let score =
+                    score.chain(model.name().and_then(|name| fuzzy_score(&self.query, name)));
+                score.min().map(|score| {
+                    let rank = self.picks.rank(model.id()).unwrap_or(usize::MAX);
+                    (rank, score, model)
+                })
+            })
+            .collect();
+        matches.sort_by_key(|&(rank, score, _)| (rank, score));
+
+        self.matches = matches.into_iter().map(|(_, _, model)| model).collect();
+        self.cursor = highlight
+            .and_then(|id| self.matches.iter().position(|model| model.id() == id))
+            .unwrap_or(0);
+    }
+
+    /// This function returns the highlighted model, if any model matches at all.
+    fn selected(&self) -> Option<&'catalog Model> {
+        self.matches.get(self.cursor).copied()
+    }
 }
 
-/// This structure links together the information from a prompt and the sliding selector it
-/// contains.
-#[expect(
-    clippy::arbitrary_source_item_ordering,
-    reason = "It's best if the prompt comes right after the text."
-)]
-#[derive(Clone)]
-struct SlidingPrompt<'contents> {
-    /// This field contains the text giving out the instructions for the prompt.
-    text: &'contents str,
-    /// This field contains the selector with a single entry per `SlidingPrompt` object.
-    selector: String,
-    /// This field contains information about whether the `text` field or the `selector` field above
-    /// are selected.
-    selected: SelectedItem,
+/// This function returns how well a search query matches a text, the lower the better, or nothing
+/// if it doesn't match at all. Texts containing the query as is always rank above the ones merely
+/// containing its characters in the same order, and the earlier and closer together the better.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let query = query.to_lowercase();
+    let text = text.to_lowercase();
+
+    if let Some(position) = text.find(&query) {
+        return Some(position);
+    }
+
+    let mut score = text.len();
+    let mut chars = text.char_indices();
+    let mut last = None;
+
+    for wanted in query.chars() {
+        let (index, _) = chars.by_ref().find(|&(_, ch)| ch == wanted)?;
+        score += last.map_or(index, |last: usize| index - last - 1);
+        last = Some(index);
+    }
+
+    Some(score)
 }
 
-impl<'contents> SlidingPrompt<'contents> {
-    /// This function creates a new sliding prompt with the default selected item set to the text
-    /// instructions.
-    const fn new(text: &'contents str, selector: String) -> Self {
-        Self {
-            text,
-            selector,
-            selected: SelectedItem::Selector,
+/// This function returns the lines describing a model in the model picker: its brand name, context
+/// length, prices, modalities and description, as far as the API lists them, followed by the
+/// filter the catalog is narrowed down with.
+fn describe_model(model: Option<&Model>, filter: Filter) -> Vec<String> {
+    let mut details = Vec::new();
+
+    match model {
+        Some(model) => {
+            if let Some(name) = model.name() {
+                details.push(name.to_owned());
+            }
+
+            let mut facts = Vec::new();
+            if let Some(context_length) = model.context_length() {
+                facts.push(format!("{context_length} tokens of context"));
+            }
+            match model.prices() {
+                _ if model.is_free() => facts.push("free".to_owned()),
+                Some((prompt, completion)) if prompt >= 0.0 && completion >= 0.0 => {
+                    facts.push(format!("${prompt:.2}/M in, ${completion:.2}/M out"));
+                }
+                Some(_) => facts.push("variable pricing".to_owned()),
+                None => {}
+            }
+            let (input, output) = model.modalities();
+            if !input.is_empty() && !output.is_empty() {
+                facts.push(format!("{} -> {}", input.join("+"), output.join("+")));
+            }
+            if !facts.is_empty() {
+                details.push(facts.join(" | "));
+            }
+
+            if let Some(description) = model.description() {
+                details.push(description.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
         }
+        None => details.push("No model matches the search and the filter".to_owned()),
     }
 
-    /// This function mutates the state of the sliding prompt to alter the currently appearing
-    /// selector field. It is thus best used with a single `SlidingPrompt` object, and a collection
-    /// of selector items to sort through and quickly change.
-    fn switch_selector(&mut self, other: String) {
-        self.selector = other;
-    }
+    details.push(format!("Showing {}", filter.label()));
+    details
 }
 
-/// This function draws and updates a frame with a prompt and a sliding selector.
-fn draw_sliding_prompt(term: &Term, prompt: &SlidingPrompt) -> Result<()> {
+/// This function draws the model picker: the search query, the page of matching models with the
+/// highlighted one in it, and the details of the latter, along with the current model and the
+/// keys to use.
+fn draw_model_picker(term: &Term, picker: &ModelPicker, current: &str) -> Result<()> {
     let (rows, cols) = term.size();
-    let upper_half_list = rows / 2 - 1;
-    let lower_half_list = rows - rows / 2 - 2;
+    let cols = cols as usize;
+    let height = page_height(term);
+    let first = picker.cursor / height * height;
+    let center =
+        |line: &str| console::pad_str(line, cols, console::Alignment::Center, None).into_owned();
 
-    for _ in 1..upper_half_list {
-        term.write_line("")?;
-    }
+    term.clear_screen()?;
+    term.write_line("")?;
 
-    let text = format!("{}", style(&prompt.text).bold());
-    let text = console::pad_str(&text, cols as usize, console::Alignment::Center, None);
-    term.write_line(&text)?;
+    let title = format!("{}", style("Select a model; type to search").bold());
+    term.write_line(&center(&title))?;
 
-    let ret;
-    let selector;
-    match prompt.selected {
-        SelectedItem::Return => {
-            selector = format!("{}", style(&prompt.selector).bold());
-            ret = format!("{}", style("Return").bold().on_cyan());
-        }
-        SelectedItem::Selector => {
-            selector = format!("{}", style(&prompt.selector).bold().on_cyan());
-            ret = format!("{}", style("Return").bold());
-        }
+    let query = format!("{}", style(format!("Search: {}_", picker.query)).bold());
+    term.write_line(&center(&query))?;
+    term.write_line("")?;
+
+    for row in 0..height {
+        let Some(model) = picker.matches.get(first + row) else {
+            term.write_line("")?;
+            continue;
+        };
+        let marker = if picker.picks.is_favorite(model.id()) {
+            "* "
+        } else if picker.picks.is_recent(model.id()) {
+            "+ "
+        } else {
+            "  "
+        };
+        let line = console::truncate_str(model.id(), cols.saturating_sub(8), "...");
+        let line = if first + row == picker.cursor {
+            format!("{}", style(format!("{marker}{line}")).bold().on_cyan())
+        } else {
+            format!("{marker}{line}")
+        };
+        term.write_line(&center(&line))?;
     }
 
-    let selector = console::pad_str(&selector, cols as usize, console::Alignment::Center, None);
-    term.write_line(&selector)?;
+    term.write_line("")?;
 
-    let ret = console::pad_str(&ret, cols as usize, console::Alignment::Center, None);
-    term.write_line(&ret)?;
+    let mut footer = describe_model(picker.selected(), picker.filter);
+    let known = picker.catalog.contains(current);
+    footer.push(format!(
+        "Current: {current}{}",
+        if known { "" } else { " (not in the catalog)" }
+    ));
+    if picker.catalog.is_stale() {
+        footer.push("The API can't be reached; the catalog may be out of date".to_owned());
+    }
+    footer.push(
+        "Enter picks, Ctrl-F pins a favorite, PgUp/PgDn scroll, Tab filters, Esc goes back"
+            .to_owned(),
+    );
 
-    for _ in 1..lower_half_list {
-        term.write_line("")?;
+    for line in footer
+        .iter()
+        .take((rows as usize).saturating_sub(height + 6))
+    {
+        let line = console::truncate_str(line, cols.saturating_sub(4), "...");
+        term.write_line(&center(&format!("{}", style(line).dim())))?;
     }
 
     Ok(())
 }
 
-/// This function takes a model value, and depending on which model is set, either changes focus
-/// from the text prompt to the model or otherwise changes the model to another one. Thus it also
-/// makes a request to the OpenRouter API to fetch the model list and display it as a sliding
-/// window.
-pub(crate) fn nav_sliding_prompt(term: &Term, model: &mut String) -> Result<()> {
-    let request: Response = ureq::get("https://openrouter.ai/api/v1/models")
-        .call()?
-        .into_body()
-        .read_json()?;
-    let models: Vec<String> = request.data.into_iter().map(|value| value.id).collect();
-    let mut prompt = SlidingPrompt::new(
-        "Select a model below; use the left and right arrow keys",
-        format!("< {model} >"),
-    );
+/// This function hands the request for the model catalog over to the network worker, animating the
+/// frame while waiting. Pressing Esc cancels the request, in which case there's no catalog. A
+/// failure is shown until a key is pressed rather than torn down the interface, and there's no
+/// catalog either.
+fn fetch_models(term: &Term, worker: &mut Worker) -> Result<Option<Arc<ModelCatalog>>> {
+    let ticket = worker.submit(Job::Models)?;
+    let mut tick = 0;
 
     loop {
-        draw_sliding_prompt(term, &prompt)?;
-
-        let key = term.read_key()?;
-        match key {
-            Key::ArrowLeft if matches!(prompt.selected, SelectedItem::Selector) => {
-                match models.get(
-                    models
-                        .iter()
-                        .position(|value| value == model)
-                        .expect("model not found")
-                        .wrapping_sub(1),
-                ) {
-                    None => {
-                        let last = models.last().expect("empty model list");
-                        prompt.switch_selector(format!("< {} >", last));
-                        model.clone_from(last);
-                    }
-                    Some(mo) => {
-                        prompt.switch_selector(format!("< {} >", mo));
-                        model.clone_from(mo);
-                    }
+        draw_waiting(term, "Fetching models", tick)?;
+        tick += 1;
+
+        if let Some(Event::Models(catalog)) = worker.poll(ticket, TICK)? {
+            match catalog {
+                Ok(catalog) => break Ok(Some(catalog)),
+                Err(err) => {
+                    draw_response(
+                        term,
+                        &format!("The models couldn't be fetched: {err}"),
+                        None,
+                    )?;
+                    draw_hint(term, "Press any key to go back")?;
+                    drop(read_key()?);
+                    break Ok(None);
                 }
             }
-            Key::ArrowRight if matches!(prompt.selected, SelectedItem::Selector) => {
-                match models.get(
-                    models
-                        .iter()
-                        .position(|value| value == model)
-                        .expect("model not found")
-                        + 1,
-                ) {
-                    None => {
-                        let first = models.first().expect("empty model list");
-                        prompt.switch_selector(format!("< {} >", first));
-                        model.clone_from(first);
-                    }
-                    Some(mo) => {
-                        prompt.switch_selector(format!("< {} >", mo));
-                        model.clone_from(mo);
-                    }
+        }
+
+        if poll_key(Duration::ZERO)? == Some(Key::Escape) {
+            worker.cancel(ticket);
+            break Ok(None);
+        }
+    }
+}
+
+/// This function lets the user pick a model from the catalog fetched by the network worker, in a
+/// list that can be searched by typing, scrolled a page at a time and narrowed down with a filter.
+/// Favorites and recently used models are pinned on top, and kept between sessions.
+pub(crate) fn nav_model_prompt(term: &Term, model: &mut String, worker: &mut Worker) -> Result<()> {
+    let Some(catalog) = fetch_models(term, worker)? else {
+        return Ok(());
+    };
+    let mut picker = ModelPicker::new(&catalog, model);
+
+    loop {
+        draw_model_picker(term, &picker, model)?;
+
+        let page = isize::try_from(page_height(term)).unwrap_or(1);
+        let highlighted = picker.selected().map(Model::id);
+        match read_key()? {
+            Key::ArrowUp => picker.move_cursor(-1),
+            Key::ArrowDown => picker.move_cursor(1),
+            Key::PageUp => picker.move_cursor(-page),
+            Key::PageDown => picker.move_cursor(page),
+            Key::Home => picker.cursor = 0,
+            Key::End => picker.move_cursor(isize::MAX),
+            Key::Tab => {
+                picker.filter = picker.filter.next();
+                picker.refresh(highlighted);
+            }
+            // This is what Ctrl-F comes in as.
+            Key::Char('\u{6}') => {
+                if let Some(id) = highlighted {
+                    picker.picks.toggle_favorite(id);
+                    // Picks are merely a convenience, so failing to keep them is no reason to
+                    // stop picking.
+                    drop(picker.picks.save());
+                    picker.refresh(Some(id));
                 }
             }
-            Key::ArrowUp if matches!(prompt.selected, SelectedItem::Selector) => {
-                prompt.selected = SelectedItem::Return;
+            Key::Char(ch) if !ch.is_control() => {
+                picker.query.push(ch);
+                picker.refresh(None);
             }
-            Key::ArrowDown if matches!(prompt.selected, SelectedItem::Selector) => {
-                prompt.selected = SelectedItem::Return;
+            Key::Backspace => {
+                let _ = picker.query.pop();
+                picker.refresh(None);
             }
-            Key::ArrowUp if matches!(prompt.selected, SelectedItem::Return) => {
-                prompt.selected = SelectedItem::Selector;
+            Key::Enter => {
+                if let Some(id) = highlighted {
+                    id.clone_into(model);
+                    picker.picks.use_model(id);
+                    drop(picker.picks.save());
+                    break;
+                }
             }
-            Key::ArrowDown if matches!(prompt.selected, SelectedItem::Return) => {
-                prompt.selected = SelectedItem::Selector;
+            Key::Escape if !picker.query.is_empty() => {
+                picker.query.clear();
+                picker.refresh(highlighted);
             }
-            Key::Enter if matches!(prompt.selected, SelectedItem::Return) => break,
+            Key::Escape => break,
             _ => {}
         }
     }
 
     Ok(())
 }
+
+/// This function returns the number of models shown at once in the model picker, which is whatever
+/// fits on the screen after leaving room for the rest of the frame.
+fn page_height(term: &Term) -> usize {
+    (term.size().0 as usize).saturating_sub(14).max(3)
+}
@@ -4,6 +4,8 @@ use anyhow::Result;
 use console::{pad_str, style, Key, Term};
 use regex::Regex;
 
+use crate::frame::input::read_key;
+
 /// This structure holds information about prompts with arbitrary user input.
 #[expect(
     clippy::arbitrary_source_item_ordering,
@@ -171,20 +173,14 @@ pub(crate) fn nav_input_prompt(
         term.clear_screen()?;
         draw_input_prompt(term, &prompt_range, &prompt_random, selected, score)?;
 
-        let key = term.read_key()?;
+        let key = read_key()?;
         match selected {
             Selected::RangePrompt if key == Key::Enter => loop {
-                let input = term.read_key()?;
+                let input = read_key()?;
                 match input {
                     Key::Escape => {
                         if validator.0.is_match(&prompt_range.prompt) {
-                            let (start, end) = prompt_range.prompt.split_at(
-                                prompt_range.prompt.find("..").expect("pattern not found"),
-                            );
-                            let end: String = end.chars().rev().collect();
-                            let (end, _) = end.split_at(end.find("..").expect("pattern not found"));
-
-                            if let (Ok(_), Ok(_)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                            if let Some((start, end)) = parse_range(&prompt_range.prompt) {
                                 if start < end {
                                     break;
                                 }
@@ -205,24 +201,17 @@ pub(crate) fn nav_input_prompt(
             Selected::RangePrompt if key == Key::ArrowUp => selected = Selected::Accept,
             Selected::RangePrompt if key == Key::ArrowDown => selected = Selected::RandomPrompt,
             Selected::RandomPrompt if key == Key::Enter => loop {
-                let input = term.read_key()?;
+                let input = read_key()?;
                 match input {
                     Key::Escape => {
-                        if validator.1.is_match(&prompt_random.prompt)
-                            && !prompt_range.prompt.is_empty()
-                        {
-                            let (start, end) = prompt_range.prompt.split_at(
-                                prompt_range.prompt.find("..").expect("pattern not found"),
-                            );
-                            let end: String = end.chars().rev().collect();
-                            let (end, _) = end.split_at(end.find("..").expect("pattern not found"));
-                            let start: usize = start.parse().expect("conversion failed");
-                            let end: usize = end.parse().expect("conversion failed");
-                            let num: usize =
-                                prompt_random.prompt.parse().expect("conversion failed");
-
-                            if num >= start && num <= end {
-                                break;
+                        if validator.1.is_match(&prompt_random.prompt) {
+                            if let (Some((start, end)), Ok(num)) = (
+                                parse_range(&prompt_range.prompt),
+                                prompt_random.prompt.parse::<usize>(),
+                            ) {
+                                if num >= start && num <= end {
+                                    break;
+                                }
                             }
                         }
                         prompt_random.prompt.clear();
@@ -240,16 +229,10 @@ pub(crate) fn nav_input_prompt(
             Selected::RandomPrompt if key == Key::ArrowUp => selected = Selected::RangePrompt,
             Selected::RandomPrompt if key == Key::ArrowDown => selected = Selected::Accept,
             Selected::Accept if key == Key::Enter => {
-                if !prompt_random.prompt.is_empty() && !prompt_range.prompt.is_empty() {
-                    let (start, end) = prompt_range
-                        .prompt
-                        .split_at(prompt_range.prompt.find("..").expect("pattern not found"));
-                    let end: String = end.chars().rev().collect();
-                    let (end, _) = end.split_at(end.find("..").expect("pattern not found"));
-                    let start: usize = start.parse().expect("conversion failed");
-                    let end: usize = end.parse().expect("conversion failed");
-                    let num: usize = prompt_random.prompt.parse().expect("conversion failed");
-
+                if let (Some((start, end)), Ok(num)) = (
+                    parse_range(&prompt_range.prompt),
+                    prompt_random.prompt.parse::<usize>(),
+                ) {
                     break Ok((num, start, end));
                 }
             }
@@ -259,3 +242,11 @@ pub(crate) fn nav_input_prompt(
         }
     }
 }
+
+/// This function splits a range in the format `n..m` into its start and end bounds, as long as
+/// both of them are valid numbers.
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let (start, end) = range.split_once("..")?;
+
+    Some((start.parse().ok()?, end.parse().ok()?))
+}
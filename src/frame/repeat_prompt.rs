@@ -4,6 +4,8 @@
 use anyhow::Result;
 use console::{pad_str, style, Key, Term};
 
+use crate::frame::input::read_key;
+
 /// This structure holds information about the entire prompt itself.
 struct Prompt<'text> {
     /// This field contains the actual input prompt to ask the user out about their decission to
@@ -98,7 +100,7 @@ pub(crate) fn nav_repeat_prompt(term: &Term) -> Result<bool> {
         term.clear_screen()?;
         draw_repeat_prompt(term, &prompt)?;
 
-        let key = term.read_key()?;
+        let key = read_key()?;
         match key {
             Key::ArrowRight | Key::ArrowLeft if prompt.selected == PromptSelectable::Prompt => {
                 match prompt.input {
@@ -0,0 +1,185 @@
+//! This module draws the answer to the result of a game: as a whole, styled after the mood it was
+//! delivered in if it has one, or line by line while it's being typed out, along with the frame
+//! shown while waiting for it and the hints shown below it.
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::Result;
+use console::{measure_text_width, pad_str, style, Term};
+
+use crate::mood::Tone;
+
+/// This constant holds the time every frame of the animation of a mood is shown for.
+const FRAME: Duration = Duration::from_millis(70);
+
+/// This function draws a frame with the answer centered on the screen, wrapped to the width of the
+/// terminal, and an optional note about how the answer was produced right below it.
+pub(crate) fn draw_response(term: &Term, text: &str, notice: Option<&str>) -> Result<()> {
+    draw_styled_response(term, text, notice, None, (0, 0, false))
+}
+
+/// This function draws the answer being typed out, rewriting only the rows it takes up now or
+/// took up the last time around, as told by the given number of lines, rather than clearing the
+/// whole screen for every piece that comes in. The number of lines it takes up now is returned. If
+/// nothing of it was drawn yet, the frame is drawn anew.
+pub(crate) fn draw_streamed_response(term: &Term, text: &str, drawn: usize) -> Result<usize> {
+    if drawn == 0 {
+        draw_response(term, text, None)?;
+        return Ok(wrap(text, term.size().1 as usize).len());
+    }
+
+    let (rows, cols) = term.size();
+    let top = |count: usize| (rows as usize / 2).saturating_sub(count / 2 + 1);
+    let lines = wrap(text, cols as usize);
+    let (start, end) = (top(lines.len()), top(lines.len()) + lines.len());
+    let first = start.min(top(drawn));
+    let last = end.max(top(drawn) + drawn);
+
+    for row in first..last {
+        term.move_cursor_to(0, row)?;
+        term.clear_line()?;
+        if let Some(line) = row.checked_sub(start).and_then(|index| lines.get(index)) {
+            let output = pad_str(line, cols as usize, console::Alignment::Center, None);
+            term.write_str(&output)?;
+        }
+    }
+
+    Ok(lines.len())
+}
+
+/// This function draws the answer in the color of the tone it was delivered in, after playing the
+/// animation of its mood, if it has one, with the note about how it was produced right below it.
+pub(crate) fn draw_toned_response(
+    term: &Term,
+    text: &str,
+    notice: Option<&str>,
+    tone: Tone,
+) -> Result<()> {
+    for frame in tone.frames() {
+        draw_styled_response(term, text, None, Some(tone), frame)?;
+        sleep(FRAME);
+    }
+
+    draw_styled_response(term, text, notice, Some(tone), (0, 0, false))
+}
+
+/// This function draws a frame with the answer styled after the given tone, if any, shifted right
+/// by the given number of columns, which may be negative, lifted by the given number of rows, and
+/// dimmed if asked to, along with the note about how it was produced.
+fn draw_styled_response(
+    term: &Term,
+    text: &str,
+    notice: Option<&str>,
+    tone: Option<Tone>,
+    (shift, lift, dim): (isize, usize, bool),
+) -> Result<()> {
+    let (rows, cols) = term.size();
+    let reach = tone.map_or(0, Tone::reach);
+    let width = (cols as usize).saturating_sub(reach * 2);
+    let lines = wrap(text, width);
+    let fill = (rows as usize / 2)
+        .saturating_sub(lines.len() / 2)
+        .saturating_sub(lift);
+    let margin = " ".repeat(reach.saturating_add_signed(shift));
+
+    term.clear_screen()?;
+
+    for _ in 1..fill {
+        term.write_line("")?;
+    }
+
+    for line in lines {
+        let mut styled = style(line);
+        if let Some(tone) = tone {
+            if let Some(color) = tone.color() {
+                styled = styled.fg(color);
+            }
+            if tone.is_bold() {
+                styled = styled.bold();
+            }
+        }
+        if dim {
+            styled = styled.dim();
+        }
+
+        let output = format!("{styled}");
+        let output = pad_str(&output, width, console::Alignment::Center, None);
+        term.write_line(&format!("{margin}{output}"))?;
+    }
+
+    if let Some(notice) = notice {
+        let notice = console::truncate_str(notice, cols as usize, "...");
+        let notice = format!("{}", style(notice).dim());
+        let output = pad_str(&notice, cols as usize, console::Alignment::Center, None);
+        term.write_line(&output)?;
+    }
+
+    Ok(())
+}
+
+/// This function draws a dim line centered right below whatever was last drawn, to hint at the keys
+/// the user can press.
+pub(crate) fn draw_hint(term: &Term, hint: &str) -> Result<()> {
+    let cols = term.size().1 as usize;
+    let hint = format!("{}", style(hint).dim());
+    let output = pad_str(&hint, cols, console::Alignment::Center, None);
+    term.write_line(&output)?;
+
+    Ok(())
+}
+
+/// This function draws a frame telling the user to wait, with an animation that moves along with
+/// every tick, and a hint on how to stop waiting.
+pub(crate) fn draw_waiting(term: &Term, text: &str, tick: usize) -> Result<()> {
+    let (rows, cols) = term.size();
+    let dots = match tick % 3 {
+        0 => ".",
+        1 => "..",
+        _ => "...",
+    };
+
+    term.clear_screen()?;
+
+    for _ in 1..rows / 2 - 1 {
+        term.write_line("")?;
+    }
+
+    let output = format!("{}", style(text).bold());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+
+    let output = format!("{}", style(dots).bold());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+
+    let output = format!("{}", style("Press Esc to cancel").dim());
+    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
+    term.write_line(&output)?;
+
+    Ok(())
+}
+
+/// This function splits a text into lines no wider than the given width, breaking lines only
+/// between words unless a single word doesn't fit on its own.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.saturating_sub(4).max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && measure_text_width(&line) + 1 + measure_text_width(word) > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
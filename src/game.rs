@@ -3,64 +3,49 @@
 //! It contains the `init()` function to initialize and start the game loop, as well as the game
 //! initialization message, some terminal configuration and the random number processor.
 
-#![expect(
-    clippy::arbitrary_source_item_ordering,
-    reason = "It's best if the run() function is kept before any functions it itself uses."
-)]
-
-use std::{sync::LazyLock, thread::sleep, time::Duration};
+use std::mem;
+use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
-use console::{pad_str, style, Term};
+use console::{Key, Term};
 use fastrand::Rng;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
-use ureq::Agent;
 
+use crate::account::Account;
+use crate::chat::{Request, Transcript};
+use crate::frame::account::nav_account;
+use crate::frame::chat::{draw_chat, Speaker};
+use crate::frame::input::{poll_key, read_key};
 use crate::frame::main_menu::{MainMenu, MainMenuAction};
 use crate::frame::options::{OptionsMenu, OptionsMenuAction};
-use crate::frame::prompt::nav_sliding_prompt;
+use crate::frame::params_prompt::nav_params_prompt;
+use crate::frame::persona_prompt::nav_persona_prompt;
+use crate::frame::prompt::nav_model_prompt;
 use crate::frame::random_prompt::nav_input_prompt;
 use crate::frame::repeat_prompt::nav_repeat_prompt;
-use crate::frame::{draw_menu, nav_menu};
-
-/// This static variable holds the message to use for the system prompt on the request builder to
-/// the chat completion request of the OpenRouter API. It is made static because the text is long
-/// and it is thus best initialized the first time it is used.
-static LLM_INPUT: LazyLock<&str> = LazyLock::new(|| {
-    "You will answer only to \"Correct\" or \"Incorrect.\" These correspond to either a\
-notification that a user got a number right in a number guessing game or not, respectively. Your\
-task is to, depending on whether you were notified they got it right, or not, to return a\
-cowboy-like answer to the user. Make it a short text. Include just your answer and nothing more.\
-Don't include emoji or otherwise non-verbal content."
-});
-
-/// This structure holds information about the messages to send to the LLM in a chat completion
-/// request to the OpenRouter API.
-#[derive(Serialize, Deserialize)]
-struct Messages {
-    /// This field contains information about the content of the specific message in question.
-    content: String,
-    /// This field contains information about who is it that is supposed to be reporting the
-    /// [`message`] field.
-    role: Role,
-}
-
-impl Messages {
-    /// This function creates a new message based on a given role for the chat exchange and the
-    /// contents of the message in question.
-    fn new(role: Role, content: &str) -> Self {
-        Self {
-            content: content.to_owned(),
-            role,
-        }
-    }
-}
+use crate::frame::response::{
+    draw_hint, draw_response, draw_streamed_response, draw_toned_response, draw_waiting,
+};
+use crate::frame::{draw_menu, draw_status, nav_menu};
+use crate::generation::{Field, Generation};
+use crate::ledger::Ledger;
+use crate::personas::{Persona, DEFAULT_PERSONA};
+use crate::prefetch::Prefetch;
+use crate::settings::{Settings, DEFAULT_MODEL};
+use crate::template::Prompts;
+use crate::worker::{Answer, Capped, Event, Job, Ticket, Worker};
+
+/// This constant holds the number of replies the player can talk back with after every round.
+const CHAT_TURNS: usize = 5;
+
+/// This constant holds the time between frames of the animations drawn while waiting.
+pub(crate) const TICK: Duration = Duration::from_millis(150);
 
 /// This enum holds the variants to the final result of the user, to better transfer between
 /// different parts of the stateful variable that the result of the current game is.
-#[derive(Clone, Copy)]
-enum RandomResult {
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum RandomResult {
     /// If the guess made by the user is correct, this variant will be used to report the status of
     /// the current game to other parts of the program.
     Correct,
@@ -69,66 +54,187 @@ enum RandomResult {
     Incorrect,
 }
 
-/// This structure is the main way of serializing information about the data we are interested in
-/// for the chat completion request to the OpenRouter API.
-#[derive(Serialize)]
-struct Request {
-    /// This field contains information about the sequence of messages to initially issue to the
-    /// LLM.
-    messages: Vec<Messages>,
-    /// This field contains information about the model to be used in the request.
-    model: String,
+impl RandomResult {
+    /// This function returns the message telling the LLM about the result alone, without the
+    /// details of the round, for the answers requested before the round is played.
+    pub(crate) const fn message(self) -> &'static str {
+        match self {
+            Self::Correct => "Correct.",
+            Self::Incorrect => "Incorrect.",
+        }
+    }
+
+    /// This function returns the name of the result, in lowercase.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Correct => "correct",
+            Self::Incorrect => "incorrect",
+        }
+    }
 }
 
-impl Request {
-    /// This function creates a new chat completion request body solely with the information
-    /// required by the program.
-    fn new(guess: RandomResult, model: &str) -> Self {
-        match guess {
-            RandomResult::Correct => Self {
-                model: model.to_owned(),
-                messages: vec![
-                    Messages::new(Role::System, *LLM_INPUT),
-                    Messages::new(Role::User, "Correct"),
-                ],
-            },
-            RandomResult::Incorrect => Self {
-                model: model.to_owned(),
-                messages: vec![
-                    Messages::new(Role::System, *LLM_INPUT),
-                    Messages::new(Role::User, "Incorrect"),
-                ],
+/// This structure holds everything that happened in a round of the game, for the LLM to refer to
+/// in its answer.
+pub(crate) struct Round {
+    /// This field contains the number the player guessed.
+    guess: usize,
+    /// This field contains the upper bound of the range the number was picked from.
+    range_end: usize,
+    /// This field contains the lower bound of the range the number was picked from.
+    range_start: usize,
+    /// This field contains whether the guess was correct.
+    result: RandomResult,
+    /// This field contains the number of rounds the player got right so far, this one included.
+    score: u32,
+    /// This field contains the number of rounds in a row with the same result, this one included.
+    streak: u32,
+    /// This field contains the number that was to be guessed.
+    target: usize,
+}
+
+impl Round {
+    /// This function returns the round with the given result, guess and number to guess, picked
+    /// from the given range, played after the rounds with the given score and the result and streak
+    /// of the last of them, if any.
+    pub(crate) fn new(
+        result: RandomResult,
+        guess: usize,
+        target: usize,
+        (range_start, range_end): (usize, usize),
+        score: u32,
+        last: Option<(RandomResult, u32)>,
+    ) -> Self {
+        Self {
+            guess,
+            range_end,
+            range_start,
+            result,
+            score: score + u32::from(result == RandomResult::Correct),
+            streak: match last {
+                Some((last, streak)) if last == result => streak + 1,
+                _ => 1,
             },
+            target,
         }
     }
-}
 
-/// This structure represents the response of a chat completion request to the OpenRouter API only
-/// with the values that the program needs.
-#[derive(Deserialize)]
-struct Response {
-    /// This field contains the vector of messages that the LLM has produced.
-    choices: Vec<ResponseMessages>,
+    /// This function returns whether the guess was correct.
+    pub(crate) const fn result(&self) -> RandomResult {
+        self.result
+    }
+
+    /// This function returns the value of the template variable with the given name, if there's
+    /// such a variable.
+    pub(crate) fn variable(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "distance" => self.guess.abs_diff(self.target).to_string(),
+            "guess" => self.guess.to_string(),
+            "range_end" => self.range_end.to_string(),
+            "range_start" => self.range_start.to_string(),
+            "score" => self.score.to_string(),
+            "streak" => self.streak.to_string(),
+            "target" => self.target.to_string(),
+            _ => return None,
+        };
+
+        Some(value)
+    }
 }
 
-/// This structure holds information about the one-level indented message containing the responses
-/// from the LLM.
-#[derive(Deserialize)]
-struct ResponseMessages {
-    /// This field contains the actual responses from the LLM.
-    message: Messages,
+/// This structure holds what lasts for the whole session of the game, through every round and every
+/// trip to the options menu: the model, persona and generation parameters answers are asked for
+/// with, the network worker asking for them, what they have cost so far, and the status of the
+/// account paying for them.
+pub(crate) struct Session<'settings> {
+    /// This field contains the status of the account behind the API key, if known, along with what
+    /// the session had cost when it was fetched, so that the credit left can be lowered by what the
+    /// session has cost since.
+    account: Option<(Account, f64)>,
+    /// This field contains the generation parameters and the preferences for providers.
+    generation: Generation,
+    /// This field contains the number of tokens the answers of the session took up, and their cost.
+    ledger: Ledger,
+    /// This field contains the model answers are asked for with.
+    model: String,
+    /// This field contains the persona narrating the game.
+    persona: Arc<Persona>,
+    /// This field contains the settings the game was started with.
+    settings: &'settings Settings,
+    /// This field contains the network worker answers are asked for through.
+    worker: Worker,
 }
 
-/// This enumeration represents the role in a chat exchange between a user and the LLM.
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum Role {
-    /// This variant represents the role of the LLM.
-    Assistant,
-    /// This variant represents the role of the system prompt.
-    System,
-    /// This variant represents the role of the user.
-    User,
+impl Session<'_> {
+    /// This function draws the summary of the status of the account in the status bar, if it's
+    /// known.
+    fn draw_status(&self, term: &Term) -> Result<()> {
+        if let Some((ref account, fetched)) = self.account {
+            draw_status(term, &account.status(self.ledger.cost() - fetched))?;
+        }
+
+        Ok(())
+    }
+
+    /// This function returns the generation parameters and the preferences for providers.
+    pub(crate) const fn generation(&self) -> &Generation {
+        &self.generation
+    }
+
+    /// This function returns the model answers are asked for with.
+    pub(crate) fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// This function returns the persona narrating the game.
+    pub(crate) const fn persona(&self) -> &Arc<Persona> {
+        &self.persona
+    }
+
+    /// This function returns the templates of the messages telling the LLM about the result of a
+    /// round.
+    pub(crate) const fn prompts(&self) -> &Prompts {
+        &self.settings.prompts
+    }
+
+    /// This function builds a request body telling the LLM the given message after the transcript
+    /// of the earlier rounds, in the voice of the persona, with the model and generation parameters
+    /// of the session.
+    pub(crate) fn request(&self, transcript: &Transcript, message: &str) -> Request {
+        Request::new(
+            &self.persona,
+            transcript,
+            message,
+            &self.model,
+            &self.generation,
+            self.settings.stream,
+        )
+    }
+
+    /// This function adds the answers the network worker threw away, since they were no longer
+    /// waited for by the time they came in, to what the session cost, as they were paid for all the
+    /// same.
+    fn settle(&mut self) {
+        for usage in self.worker.take_discarded() {
+            self.ledger.record(usage);
+        }
+    }
+
+    /// This function takes note of an answer having been shown, in the voice of the persona, so
+    /// that the answers picked for being the most novel are told apart from the ones shown
+    /// recently. Only answers that were generated, rather than taken from the canned lines or the
+    /// bank of answers, are taken note of.
+    fn shown(&mut self, answer: &Answer) -> Result<()> {
+        if !answer.is_generated() {
+            return Ok(());
+        }
+
+        self.worker.shown(self.persona.id(), answer.text())
+    }
+
+    /// This function returns the network worker answers are asked for through.
+    pub(crate) const fn worker(&mut self) -> &mut Worker {
+        &mut self.worker
+    }
 }
 
 /// Initializes the game state and handles literally everything. This is a `main()` function of
@@ -144,9 +250,22 @@ enum Role {
 /// - [`Regex::Error`]
 /// - [`ureq::Error`]
 /// - [`std::io::Error`]
-pub fn run(model: Option<String>, api_key: &str) -> Result<()> {
+pub fn run(settings: Settings) -> Result<()> {
     let term = Term::stdout();
-    let mut model = model.unwrap_or_else(|| "qwen/qwen3-32b:free".to_owned());
+    let mut session = Session {
+        account: settings.account.clone().map(|account| (account, 0.0)),
+        generation: settings.generation.clone(),
+        ledger: Ledger::default(),
+        model: settings
+            .model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_MODEL.to_owned()),
+        persona: settings
+            .personas
+            .get(settings.persona.as_deref().unwrap_or(DEFAULT_PERSONA)),
+        settings: &settings,
+        worker: Worker::spawn(&settings),
+    };
     let mut main_menu = MainMenu::Play;
     let mut options_menu = OptionsMenu::Model;
 
@@ -154,31 +273,107 @@ pub fn run(model: Option<String>, api_key: &str) -> Result<()> {
 
     loop {
         draw_menu(&term, &main_menu)?;
+        session.draw_status(&term)?;
 
         match nav_menu(&term, &mut main_menu)? {
-            MainMenuAction::Pass => continue,
+            MainMenuAction::Pass => {}
             MainMenuAction::Finish => break,
-            MainMenuAction::OptionsPage => options(&term, &mut options_menu, &mut model)?,
-            MainMenuAction::StartGame => init_game(&term, &model, api_key)?,
+            MainMenuAction::OptionsPage => options(&term, &mut options_menu, &mut session)?,
+            MainMenuAction::StartGame => init_game(&term, &mut session)?,
         }
     }
 
     term.clear_screen()?;
+    session.settle();
+
+    if let Some(summary) = session.ledger.summary() {
+        term.write_line(&summary)?;
+    }
 
     Ok(())
 }
 
-/// This function renders the options menu.
-fn options(term: &Term, menu: &mut OptionsMenu, model: &mut String) -> Result<()> {
+/// This function returns what's shown while the network worker holds back a request so as not to
+/// exceed the quota of the API, with the number of seconds left until it's let through.
+fn breather(narrator: &str, left: Duration) -> String {
+    format!(
+        "The {}'s catching his breath; back in {}s",
+        narrator.to_lowercase(),
+        left.as_millis().div_ceil(1000)
+    )
+}
+
+/// This function makes sure no more money is spent once the answers of the session have cost as
+/// much as the spend cap allows, after the given answer. Since the answer may have come from a
+/// model fallen back to, the network worker, which holds the catalog of models, drops the models to
+/// fall back to that aren't free, and switches over to a free model or to answering offline if the
+/// model picked isn't free. It returns a note about what changed, if anything did.
+fn enforce_cap(session: &mut Session<'_>, answer: &Answer) -> Result<Option<String>> {
+    let Some(cap) = session
+        .settings
+        .spend_cap
+        .filter(|&cap| session.ledger.cost() >= cap && answer.model().is_some())
+    else {
+        return Ok(None);
+    };
+
+    let ticket = session.worker.submit(Job::Cap(session.model.clone()))?;
+    let capped = loop {
+        if let Some(Event::Capped(capped)) = session.worker.poll(ticket, TICK)? {
+            break capped;
+        }
+    };
+
+    Ok(match capped {
+        Capped::Offline => Some(format!(
+            "The spend cap of ${cap:.2} is reached; answering offline from now on"
+        )),
+        Capped::Switched(free) => {
+            let notice = format!("The spend cap of ${cap:.2} is reached; switched over to {free}");
+            session.model = free;
+            Some(notice)
+        }
+        Capped::Trimmed => Some(format!(
+            "The spend cap of ${cap:.2} is reached; dropped the fallback models that aren't free"
+        )),
+        Capped::Unchanged => None,
+    })
+}
+
+/// This function renders the options menu, which changes the model, the persona narrating the
+/// game, the generation parameters and the preferences for providers, and shows the status of the
+/// account behind the API key, along with a summary of it in the status bar.
+fn options(term: &Term, menu: &mut OptionsMenu, session: &mut Session<'_>) -> Result<()> {
     loop {
         draw_menu(term, menu)?;
+        session.draw_status(term)?;
 
         match nav_menu(term, menu)? {
+            OptionsMenuAction::ChangeGeneration => nav_params_prompt(
+                term,
+                "Generation parameters",
+                Field::GENERATION,
+                &mut session.generation,
+            )?,
             OptionsMenuAction::ChangeModel => {
-                nav_sliding_prompt(term, model)?;
+                nav_model_prompt(term, &mut session.model, &mut session.worker)?;
+            }
+            OptionsMenuAction::ChangePersona => {
+                nav_persona_prompt(term, &session.settings.personas, &mut session.persona)?;
             }
+            OptionsMenuAction::ChangeProviders => nav_params_prompt(
+                term,
+                "Provider preferences",
+                Field::PROVIDER,
+                &mut session.generation,
+            )?,
             OptionsMenuAction::GoBack => break,
-            OptionsMenuAction::Pass => continue,
+            OptionsMenuAction::Pass => {}
+            OptionsMenuAction::ShowAccount => {
+                if let Some(account) = nav_account(term, &mut session.worker)? {
+                    session.account = Some((account, session.ledger.cost()));
+                }
+            }
         }
     }
 
@@ -187,123 +382,265 @@ fn options(term: &Term, menu: &mut OptionsMenu, model: &mut String) -> Result<()
 
 /// This function initializes the game loop and processes all logic involved in the game itself
 /// until the user decides to exit it.
-fn init_game(term: &Term, model: &str, api_key: &str) -> Result<()> {
+fn init_game(term: &Term, session: &mut Session<'_>) -> Result<()> {
     let ranged_re = Regex::new(r"\A\d+\.\.\d+\z")?;
     let random_re = Regex::new(r"\A\d+\z")?;
     let mut rng = Rng::new();
     let mut score = 0;
+    let mut last = None;
+    let mut transcript = Transcript::default();
+    let mut prefetch = session.settings.prefetch.then(Prefetch::default);
 
     loop {
+        if let Some(ref mut prefetch) = prefetch {
+            prefetch.fill(session, &transcript, score, last)?;
+        }
+
         let (guess, range_start, range_end) =
             nav_input_prompt(term, (&ranged_re, &random_re), score)?;
 
-        let result = process_random((range_start, range_end), guess, &mut rng);
-        let message = process_request(term, model, api_key, result)?;
+        let (result, target) = process_random((range_start, range_end), guess, &mut rng);
+        let round = Round::new(result, guess, target, (range_start, range_end), score, last);
+        let (ticket, prompt) = match prefetch.as_mut().and_then(|prefetch| prefetch.take(result)) {
+            Some((ticket, prompt)) => (Some(ticket), prompt),
+            None => (None, session.settings.prompts.render(&round)),
+        };
+        let Some(answer) = process_request(term, session, &round, &prompt, &transcript, ticket)?
+        else {
+            continue;
+        };
+        let (message, notice, usage) = (answer.text(), answer.notice(), answer.usage());
+
+        session.settle();
+        session.shown(&answer)?;
+        transcript.record(&prompt, message);
+        score = round.score;
+        last = Some((round.result, round.streak));
+
+        let switched = match usage {
+            Some(usage) => {
+                session.ledger.record(usage);
+                enforce_cap(session, &answer)?
+            }
+            None => None,
+        };
 
-        match result {
-            RandomResult::Correct => score += 1,
-            RandomResult::Incorrect => {}
+        match answer.tone() {
+            Some(tone) => draw_toned_response(term, message, notice, tone)?,
+            None => draw_response(term, message, notice)?,
         }
-
-        term.clear_screen()?;
-        let (rows, cols) = term.size();
-        for _ in 1..rows / 2 {
-            term.write_line("")?;
+        if let Some(usage) = usage {
+            draw_hint(
+                term,
+                &session.ledger.status(usage, session.settings.spend_cap),
+            )?;
         }
+        if let Some(ref switched) = switched {
+            draw_hint(term, switched)?;
+        }
+        draw_hint(term, "Press T to talk back, or any other key to go on")?;
 
-        let output = pad_str(&message, cols as usize, console::Alignment::Center, None);
-        term.write_line(&output)?;
-        sleep(Duration::from_secs(5));
+        if let Some(Key::Char('t' | 'T')) = poll_key(Duration::from_secs(5))? {
+            talk(term, session, round.result, message, &mut transcript)?;
+        }
 
         if !nav_repeat_prompt(term)? {
             break;
         }
     }
 
+    if let Some(ref mut prefetch) = prefetch {
+        prefetch.clear(&mut session.worker);
+    }
+
     Ok(())
 }
 
 /// This functions takes the role of number generator, as it takes both inputs from the user per
 /// game, and both produces the number to be guessed within the given range, and matches the user
-/// input to such number.
-fn process_random(range: (usize, usize), input: usize, rng: &mut Rng) -> RandomResult {
+/// input to such number. The number is returned along with the result.
+fn process_random(range: (usize, usize), input: usize, rng: &mut Rng) -> (RandomResult, usize) {
     let random = rng.usize(range.0..=range.1);
 
     match input {
-        _ if input == random => RandomResult::Correct,
-        _ => RandomResult::Incorrect,
+        _ if input == random => (RandomResult::Correct, random),
+        _ => (RandomResult::Incorrect, random),
     }
 }
 
-/// This function builds a request body and processes a chat completion request to the OpenRouter
-/// API.
+/// This function builds a request body telling the LLM about the round, along with the transcript
+/// of the earlier ones, and hands it over to the network worker for an answer, animating the frame
+/// while waiting. When streaming, the answer is typed out on screen as it comes in. Pressing Esc
+/// cancels the request, in which case there's no answer. The answer comes along with the note about
+/// how it was produced and the number of tokens it took up, if it went through an LLM.
+///
+/// If the ticket of an answer requested ahead of time is given, that one is waited for instead of
+/// requesting another.
 fn process_request(
     term: &Term,
-    model: &str,
-    api_key: &str,
-    result: RandomResult,
-) -> Result<String> {
-    let request_body = Request::new(result, model);
-    let agent = Agent::new_with_defaults();
-    let (rows, cols) = term.size();
-    let (dot1, dot2, dot3) = (
-        format!("{}", style(".").bold()),
-        format!("{}", style("..").bold()),
-        format!("{}", style("...").bold()),
-    );
+    session: &mut Session<'_>,
+    round: &Round,
+    message: &str,
+    transcript: &Transcript,
+    ticket: Option<Ticket>,
+) -> Result<Option<Answer>> {
+    let ticket = if let Some(ticket) = ticket {
+        ticket
+    } else {
+        let request = session.request(transcript, message);
+        session
+            .worker
+            .submit(Job::Complete(Box::new(request), round.result()))?
+    };
+    let worker = &mut session.worker;
+    let mut partial = String::new();
+    let mut drawn = 0;
+    let mut tick = 0;
 
-    term.clear_screen()?;
     term.hide_cursor()?;
 
-    for _ in 1..rows / 2 - 1 {
-        term.write_line("")?;
+    loop {
+        if partial.is_empty() {
+            match worker.held() {
+                Some(left) => draw_waiting(term, &breather(session.persona.name(), left), tick)?,
+                None => draw_waiting(term, "Processing", tick)?,
+            }
+            tick += 1;
+        }
+
+        match worker.poll(ticket, TICK)? {
+            Some(Event::Answer(answer)) => break Ok(Some(answer?)),
+            Some(Event::Piece(piece)) => {
+                partial.push_str(&piece);
+                drawn = draw_streamed_response(term, &partial, drawn)?;
+            }
+            Some(Event::Restart) => {
+                partial.clear();
+                drawn = 0;
+            }
+            Some(Event::Account(_) | Event::Capped(_) | Event::Models(_)) | None => {}
+        }
+
+        if poll_key(Duration::ZERO)? == Some(Key::Escape) {
+            worker.cancel(ticket);
+            break Ok(None);
+        }
     }
+}
 
-    let output = format!("{}", style("Processing").bold());
-    let output = pad_str(&output, cols as usize, console::Alignment::Center, None);
-    term.write_line(&output)?;
-    sleep(Duration::from_millis(100));
+/// This function lets the player talk back to the persona narrating the game after a round, for up
+/// to a few replies, until they press Esc to go back to the game. Every reply is answered through
+/// the network worker, and recorded in the transcript along with its answer, and in the ledger
+/// along with the number of tokens it took up.
+fn talk(
+    term: &Term,
+    session: &mut Session<'_>,
+    result: RandomResult,
+    answer: &str,
+    transcript: &mut Transcript,
+) -> Result<()> {
+    let mut lines = vec![(Speaker::Narrator, answer.to_owned())];
+    let mut input = String::new();
+    let mut switched = None;
+    let mut turns = 0;
 
     loop {
-        let output = pad_str(&dot1, cols as usize, console::Alignment::Center, None);
-        term.write_line(&output)?;
-
-        let response = agent
-            .post("https://openrouter.ai/api/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .send_json(&request_body);
-
-        term.move_cursor_up(1)?;
-        term.clear_line()?;
-        let output = pad_str(&dot2, cols as usize, console::Alignment::Center, None);
-        term.write_line(&output)?;
-
-        match response {
-            Ok(response) => {
-                let response: Response = response.into_body().read_json()?;
-                let output = &response
-                    .choices
-                    .last()
-                    .expect("empty vector")
-                    .message
-                    .content;
-
-                if !output.is_empty() {
-                    break Ok(output.to_owned());
+        let status = if let Some(switched) = switched.take() {
+            switched
+        } else if turns < CHAT_TURNS {
+            format!(
+                "{} replies left; Enter sends, Esc goes back to the game",
+                CHAT_TURNS - turns
+            )
+        } else {
+            "That's all the talking for this round; press Esc to go back to the game".to_owned()
+        };
+        let open = (turns < CHAT_TURNS).then_some(input.as_str());
+        draw_chat(term, session.persona.name(), &lines, open, &status)?;
+
+        match read_key()? {
+            Key::Escape => break,
+            Key::Enter if turns < CHAT_TURNS && !input.trim().is_empty() => {
+                let reply = mem::take(&mut input).trim().to_owned();
+                let request = session.request(transcript, &reply);
+                lines.push((Speaker::Player, reply.clone()));
+                turns += 1;
+
+                if let Some(answer) = converse(term, session, request, result, &mut lines)? {
+                    session.shown(&answer)?;
+                    transcript.record(&reply, answer.text());
+                    if let Some(usage) = answer.usage() {
+                        session.ledger.record(usage);
+                        switched = enforce_cap(session, &answer)?;
+                    }
                 }
             }
-            Err(err) => {
-                break Err(err.into());
+            Key::Backspace => {
+                let _ = input.pop();
             }
+            Key::Char(ch) if !ch.is_control() && turns < CHAT_TURNS => input.push(ch),
+            _ => {}
         }
+    }
+
+    Ok(())
+}
 
-        sleep(Duration::from_millis(100));
-        term.move_cursor_up(1)?;
-        term.clear_line()?;
-        let output = pad_str(&dot3, cols as usize, console::Alignment::Center, None);
-        term.write_line(&output)?;
-        sleep(Duration::from_millis(100));
-        term.move_cursor_up(1)?;
-        term.clear_line()?;
+/// This function hands a reply of the player over to the network worker for an answer, typing the
+/// answer out in the conversation as it comes in. Pressing Esc cancels the request, in which case
+/// there's no answer, and neither is there if the request fails. The answer comes along with the
+/// number of tokens it took up, if it went through an LLM.
+fn converse(
+    term: &Term,
+    session: &mut Session<'_>,
+    request: Request,
+    result: RandomResult,
+    lines: &mut Vec<(Speaker, String)>,
+) -> Result<Option<Answer>> {
+    let narrator = session.persona.name();
+    let worker = &mut session.worker;
+    let ticket = worker.submit(Job::Complete(Box::new(request), result))?;
+    let mut partial = String::new();
+    let mut tick = 0;
+
+    lines.push((Speaker::Narrator, String::new()));
+
+    loop {
+        let status = worker.held().map_or_else(
+            || "Press Esc to cancel".to_owned(),
+            |left| format!("{}; press Esc to cancel", breather(narrator, left)),
+        );
+        if let Some(&mut (_, ref mut line)) = lines.last_mut() {
+            if partial.is_empty() {
+                line.clear();
+                line.push_str(&".".repeat(tick % 3 + 1));
+                tick += 1;
+            } else {
+                line.clone_from(&partial);
+            }
+        }
+        draw_chat(term, narrator, lines, None, &status)?;
+
+        match worker.poll(ticket, TICK)? {
+            Some(Event::Answer(answer)) => {
+                let (line, answer) = match answer {
+                    Ok(answer) => (answer.text().to_owned(), Some(answer)),
+                    Err(err) => (format!("(no answer: {err})"), None),
+                };
+                if let Some(&mut (_, ref mut last)) = lines.last_mut() {
+                    *last = line;
+                }
+                break Ok(answer);
+            }
+            Some(Event::Piece(piece)) => partial.push_str(&piece),
+            Some(Event::Restart) => partial.clear(),
+            Some(Event::Account(_) | Event::Capped(_) | Event::Models(_)) | None => {}
+        }
+
+        if poll_key(Duration::ZERO)? == Some(Key::Escape) {
+            worker.cancel(ticket);
+            drop(lines.pop());
+            break Ok(None);
+        }
     }
 }
@@ -0,0 +1,317 @@
+//! This module holds the parameters the LLM generates its answers with, along with the preferences
+//! for the providers the OpenRouter API routes requests to, all of them sent along every chat
+//! completion request.
+//!
+//! Every parameter is optional, and those left unset are left out of the request, so that the
+//! defaults of the model apply.
+
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// This constant holds the number of stop sequences the API takes at most.
+const MAX_STOP: usize = 4;
+
+/// This enumeration holds the parameters that can be changed in the options menu, be it generation
+/// parameters or provider preferences.
+#[derive(Clone, Copy)]
+pub(crate) enum Field {
+    /// This variant is used for whether other providers may be used when the preferred ones fail.
+    AllowFallbacks,
+    /// This variant is used for the providers never to use.
+    Ignore,
+    /// This variant is used for the maximum number of tokens in an answer.
+    MaxTokens,
+    /// This variant is used for the only providers to use.
+    Only,
+    /// This variant is used for the providers to try first, in order.
+    Order,
+    /// This variant is used for the seed of the sampling.
+    Seed,
+    /// This variant is used for how providers are sorted when none is preferred.
+    Sort,
+    /// This variant is used for the sequences that stop the answer.
+    Stop,
+    /// This variant is used for the temperature of the sampling.
+    Temperature,
+    /// This variant is used for the nucleus sampling threshold.
+    TopP,
+}
+
+impl Field {
+    /// This constant holds the generation parameters, in the order they are listed in.
+    pub(crate) const GENERATION: &[Self] = &[
+        Self::Temperature,
+        Self::TopP,
+        Self::MaxTokens,
+        Self::Seed,
+        Self::Stop,
+    ];
+
+    /// This constant holds the provider preferences, in the order they are listed in.
+    pub(crate) const PROVIDER: &[Self] = &[
+        Self::Order,
+        Self::Only,
+        Self::Ignore,
+        Self::Sort,
+        Self::AllowFallbacks,
+    ];
+
+    /// This function returns a hint at the values the parameter takes.
+    pub(crate) const fn hint(self) -> &'static str {
+        match self {
+            Self::AllowFallbacks => "yes or no",
+            Self::Ignore | Self::Only | Self::Order => "provider names, separated by commas",
+            Self::MaxTokens => "a whole number above zero",
+            Self::Seed => "a whole number",
+            Self::Sort => "price, throughput or latency",
+            Self::Stop => "up to 4 sequences, separated by commas; \\n for a line break",
+            Self::Temperature => "a number from 0 to 2; higher is wilder",
+            Self::TopP => "a number above 0, up to 1",
+        }
+    }
+
+    /// This function returns the name of the parameter to show to the user.
+    pub(crate) const fn label(self) -> &'static str {
+        match self {
+            Self::AllowFallbacks => "Fallback providers",
+            Self::Ignore => "Ignored providers",
+            Self::MaxTokens => "Max tokens",
+            Self::Only => "Only providers",
+            Self::Order => "Provider order",
+            Self::Seed => "Seed",
+            Self::Sort => "Provider sort",
+            Self::Stop => "Stop sequences",
+            Self::Temperature => "Temperature",
+            Self::TopP => "Top P",
+        }
+    }
+}
+
+/// This structure holds the parameters the LLM generates its answers with.
+#[derive(Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Generation {
+    /// This field contains the maximum number of tokens in an answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// This field contains the preferences for the providers the request is routed to, given
+    /// through the `[generation.provider]` table.
+    #[serde(skip_serializing_if = "Provider::is_empty")]
+    pub provider: Provider,
+    /// This field contains the seed of the sampling, for answers that can be repeated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// This field contains the sequences that stop the answer as soon as the LLM produces them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    /// This field contains the temperature of the sampling, from 0 to 2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// This field contains the nucleus sampling threshold, above 0 and up to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+}
+
+impl Generation {
+    /// This function returns the value of the given parameter as it's shown to the user, which is
+    /// empty if it's unset.
+    pub(crate) fn get(&self, field: Field) -> String {
+        match field {
+            Field::AllowFallbacks => self
+                .provider
+                .allow_fallbacks
+                .map(|allow| if allow { "yes" } else { "no" }.to_owned())
+                .unwrap_or_default(),
+            Field::Ignore => self.provider.ignore.join(", "),
+            Field::MaxTokens => show(self.max_tokens),
+            Field::Only => self.provider.only.join(", "),
+            Field::Order => self.provider.order.join(", "),
+            Field::Seed => show(self.seed),
+            Field::Sort => show(self.provider.sort),
+            Field::Stop => self
+                .stop
+                .iter()
+                .map(|stop| stop.replace('\n', "\\n"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Field::Temperature => show(self.temperature),
+            Field::TopP => show(self.top_p),
+        }
+    }
+
+    /// This function sets the given parameter from its value as typed in by the user, unsetting it
+    /// if the value is empty, and returns why the value is invalid if it is, leaving the parameter
+    /// as it was.
+    pub(crate) fn set(&mut self, field: Field, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let mut changed = self.clone();
+
+        match field {
+            Field::AllowFallbacks => {
+                changed.provider.allow_fallbacks = match value.to_ascii_lowercase().as_str() {
+                    "" => None,
+                    "yes" | "true" => Some(true),
+                    "no" | "false" => Some(false),
+                    _ => return Err(format!("`{value}` is neither yes nor no")),
+                };
+            }
+            Field::Ignore => changed.provider.ignore = split(value),
+            Field::MaxTokens => changed.max_tokens = parse(value)?,
+            Field::Only => changed.provider.only = split(value),
+            Field::Order => changed.provider.order = split(value),
+            Field::Seed => changed.seed = parse(value)?,
+            Field::Sort => changed.provider.sort = parse(value)?,
+            Field::Stop => {
+                changed.stop = split(value)
+                    .into_iter()
+                    .map(|stop| stop.replace("\\n", "\n"))
+                    .collect();
+            }
+            Field::Temperature => changed.temperature = parse(value)?,
+            Field::TopP => changed.top_p = parse(value)?,
+        }
+
+        changed.validate()?;
+        *self = changed;
+
+        Ok(())
+    }
+
+    /// This function checks that every parameter that is set is within the bounds the API takes.
+    ///
+    /// # Errors
+    ///
+    /// The function returns the reason why the first parameter out of bounds is invalid, if any.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(temperature) = self
+            .temperature
+            .filter(|&value| !(0.0..=2.0).contains(&value))
+        {
+            return Err(format!(
+                "the temperature must be from 0 to 2, not {temperature}"
+            ));
+        }
+        if let Some(top_p) = self
+            .top_p
+            .filter(|&value| value <= 0.0 || value > 1.0 || value.is_nan())
+        {
+            return Err(format!("top P must be above 0 and up to 1, not {top_p}"));
+        }
+        if self.max_tokens == Some(0) {
+            return Err("the maximum number of tokens must be above zero".to_owned());
+        }
+        if self.stop.len() > MAX_STOP {
+            return Err(format!(
+                "there can be up to {MAX_STOP} stop sequences, not {}",
+                self.stop.len()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// This structure holds the preferences for the providers the OpenRouter API routes requests to.
+/// Other APIs have no use for them, so they are better left unset.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Provider {
+    /// This field contains whether other providers may be used when the preferred ones fail.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+    /// This field contains the providers never to use.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// This field contains the only providers to use.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+    /// This field contains the providers to try first, in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    /// This field contains how providers are sorted when none of them is preferred.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProviderSort>,
+}
+
+impl Provider {
+    /// This function returns whether there are no preferences at all, in which case they are left
+    /// out of the request.
+    const fn is_empty(&self) -> bool {
+        self.allow_fallbacks.is_none()
+            && self.ignore.is_empty()
+            && self.only.is_empty()
+            && self.order.is_empty()
+            && self.sort.is_none()
+    }
+}
+
+/// This enumeration holds the ways providers can be sorted in when none of them is preferred.
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderSort {
+    /// This variant is used to prefer the providers with the lowest latency.
+    Latency,
+    /// This variant is used to prefer the cheapest providers.
+    Price,
+    /// This variant is used to prefer the providers with the highest throughput.
+    Throughput,
+}
+
+impl Display for ProviderSort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Self::Latency => "latency",
+            Self::Price => "price",
+            Self::Throughput => "throughput",
+        })
+    }
+}
+
+impl FromStr for ProviderSort {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "latency" => Ok(Self::Latency),
+            "price" => Ok(Self::Price),
+            "throughput" => Ok(Self::Throughput),
+            _ => Err(format!("`{value}` is none of price, throughput or latency")),
+        }
+    }
+}
+
+/// This function parses the value of a parameter, which is unset if the value is empty.
+fn parse<T>(value: &str) -> Result<Option<T>, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    value
+        .parse()
+        .map(Some)
+        .map_err(|err| format!("`{value}` is invalid: {err}"))
+}
+
+/// This function shows the value of a parameter, which is empty if it's unset.
+fn show<T>(value: Option<T>) -> String
+where
+    T: Display,
+{
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// This function splits a list of values separated by commas, leaving out the empty ones.
+fn split(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
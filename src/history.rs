@@ -0,0 +1,105 @@
+//! This module holds the answers shown recently in the voice of each persona, kept between sessions
+//! in the state directory of the game, so that new answers can be told apart from the ones the
+//! player has already seen.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// This constant holds the number of recent answers to remember for each persona.
+const RECENT_LIMIT: usize = 20;
+
+/// This structure holds the answers shown recently, by the identifier of the persona they were in
+/// the voice of.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct History {
+    /// This field contains the answers shown recently for each persona, the most recent one first.
+    personas: BTreeMap<String, Vec<String>>,
+}
+
+impl History {
+    /// This function reads the history kept in the state directory. Since it's merely a way of
+    /// telling answers apart, a missing or unreadable history makes for an empty one.
+    pub(crate) fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// This function returns how new the given answer is to the player, from 0 for an answer they
+    /// have just seen to 1 for one that shares no words with any recent answer of the persona with
+    /// the given identifier.
+    pub(crate) fn novelty(&self, persona: &str, answer: &str) -> f64 {
+        let words = words(answer);
+
+        self.personas
+            .get(persona)
+            .into_iter()
+            .flatten()
+            .map(|recent| 1.0 - similarity(&words, &self::words(recent)))
+            .fold(1.0, f64::min)
+    }
+
+    /// This function records the given answer as the most recent one shown for the persona with
+    /// the given identifier.
+    pub(crate) fn record(&mut self, persona: &str, answer: &str) {
+        let recent = self.personas.entry(persona.to_owned()).or_default();
+
+        recent.retain(|recent| recent != answer);
+        recent.insert(0, answer.to_owned());
+        recent.truncate(RECENT_LIMIT);
+    }
+
+    /// This function writes the history to the state directory, creating it if needed.
+    pub(crate) fn save(&self) -> Result<()> {
+        let Some(path) = path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+
+        fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// This function returns the location of the file the history is kept in.
+fn path() -> Option<PathBuf> {
+    paths::state_dir().map(|dir| dir.join("history.toml"))
+}
+
+/// This function returns how alike two answers are, as the share of their distinct words they have
+/// in common, from 0 to 1.
+fn similarity(left: &BTreeSet<String>, right: &BTreeSet<String>) -> f64 {
+    let union = left.union(right).count();
+    if union == 0 {
+        return 1.0;
+    }
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "word counts are nowhere near the point of losing precision"
+    )]
+    let share = left.intersection(right).count() as f64 / union as f64;
+
+    share
+}
+
+/// This function splits an answer into its distinct words, in lowercase and without punctuation.
+fn words(answer: &str) -> BTreeSet<String> {
+    answer
+        .split(|ch: char| !ch.is_alphanumeric() && ch != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
@@ -0,0 +1,87 @@
+//! This module keeps count of the tokens the answers of a session take up, and of what they cost,
+//! for the status line shown after every answer and the summary shown at the end of the session.
+
+use crate::chat::Usage;
+
+/// This structure holds the number of tokens all the answers of a session took up, and their cost.
+#[derive(Default)]
+pub(crate) struct Ledger {
+    /// This field contains the number of answers that went through an LLM.
+    answers: u32,
+    /// This field contains the number of tokens in all the answers.
+    completion_tokens: u64,
+    /// This field contains the cost of all the answers whose cost is known, in dollars.
+    cost: f64,
+    /// This field contains the number of tokens in all the requests.
+    prompt_tokens: u64,
+    /// This field contains the number of answers whose cost is unknown.
+    unpriced: u32,
+}
+
+impl Ledger {
+    /// This function returns the cost of all the answers whose cost is known, in dollars.
+    pub(crate) const fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    /// This function adds an answer that took up the given number of tokens to the session.
+    pub(crate) fn record(&mut self, usage: Usage) {
+        self.answers += 1;
+        self.completion_tokens += usage.completion_tokens();
+        self.prompt_tokens += usage.prompt_tokens();
+
+        match usage.cost() {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced += 1,
+        }
+    }
+
+    /// This function returns the cost of the session as it's shown to the user, hinting at whether
+    /// the cost of some of the answers is unknown.
+    fn session_cost(&self) -> String {
+        if self.unpriced == self.answers {
+            dollars(None)
+        } else if self.unpriced > 0 {
+            format!("over {}", dollars(Some(self.cost)))
+        } else {
+            dollars(Some(self.cost))
+        }
+    }
+
+    /// This function returns the status line for the last answer, which took up the given number
+    /// of tokens, along with the totals of the session and the spend cap, if there's one.
+    pub(crate) fn status(&self, last: Usage, cap: Option<f64>) -> String {
+        let mut status = format!(
+            "This round: {} tokens, {}; session: {} tokens, {}",
+            last.prompt_tokens() + last.completion_tokens(),
+            dollars(last.cost()),
+            self.prompt_tokens + self.completion_tokens,
+            self.session_cost(),
+        );
+
+        if let Some(cap) = cap {
+            status.push_str(" of ");
+            status.push_str(&dollars(Some(cap)));
+        }
+
+        status
+    }
+
+    /// This function returns the summary of the session, unless no answer went through an LLM.
+    pub(crate) fn summary(&self) -> Option<String> {
+        (self.answers > 0).then(|| {
+            format!(
+                "{} answers took up {} prompt and {} completion tokens, for a cost of {}.",
+                self.answers,
+                self.prompt_tokens,
+                self.completion_tokens,
+                self.session_cost(),
+            )
+        })
+    }
+}
+
+/// This function formats a cost in dollars, if it's known.
+fn dollars(cost: Option<f64>) -> String {
+    cost.map_or_else(|| "unknown cost".to_owned(), |cost| format!("${cost:.4}"))
+}
@@ -1,5 +1,6 @@
 //! The library components of the game. They allow initializing the game, taking input, processing a
-//! random number and fetching a response from the OpenRouter API.
+//! random number and fetching a response from one of the backends, the OpenRouter API being the
+//! default one.
 //!
 //! The starting point of the library is the game.rs file, which contains the main game loop.
 
@@ -8,7 +9,36 @@
     reason = "clap is not used in the library crate, but it is used in the binary crate."
 )]
 
+mod account;
+mod backend;
+mod bank;
+mod catalog;
+mod chat;
+mod config;
 mod frame;
 mod game;
+mod generation;
+mod history;
+mod ledger;
+mod mood;
+mod paths;
+mod personas;
+mod picks;
+mod prefetch;
+mod sanitize;
+mod secret;
+mod settings;
+mod template;
+mod throttle;
+mod worker;
 
+pub use account::Account;
+pub use bank::Bank;
+pub use catalog::ModelCatalog;
+pub use config::Config;
 pub use game::run;
+pub use generation::{Generation, Provider, ProviderSort};
+pub use personas::Personas;
+pub use secret::ApiKey;
+pub use settings::{Backend, Settings, DEFAULT_BASE_URL, DEFAULT_MODEL};
+pub use template::Prompts;
@@ -17,85 +17,565 @@
     reason = "The dependencies are used in the library crate."
 )]
 
-use anyhow::Result;
-use clap::Parser;
-use serde::Deserialize;
+use core::mem;
+use core::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context as _, Result};
+use clap::{error::ErrorKind, CommandFactory as _, Parser, Subcommand, ValueEnum};
+use console::Term;
+use randyrand::{
+    Account, ApiKey, Backend, Bank, Config, Generation, ModelCatalog, Personas, ProviderSort,
+    Settings, DEFAULT_BASE_URL,
+};
+
+/// This constant holds the number of candidate answers that can be asked for every round at most.
+const MAX_CANDIDATES: i64 = 8;
+
+/// This enumeration holds the backends that can be picked through the command-line to answer the
+/// result of every game.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BackendKind {
+    /// Answer with lines drawn from the bank built with `randy bank build`, without any network
+    /// access.
+    Bank,
+    /// Run the command given in `--backend-command` to produce each answer.
+    Command,
+    /// Answer with the message that would otherwise be sent to the model.
+    Mock,
+    /// Answer with canned cowboy lines, without any network access.
+    Offline,
+    /// Answer through the OpenRouter API.
+    #[value(name = "openrouter")]
+    OpenRouter,
+}
+
+/// This enumeration holds the subcommands, for anything other than playing the game.
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the bank of answers generated ahead of time.
+    Bank {
+        /// What to do with the bank.
+        #[command(subcommand)]
+        command: BankCommand,
+    },
+}
+
+/// This enumeration holds the subcommands managing the bank of answers.
+#[derive(Subcommand)]
+enum BankCommand {
+    /// Ask the model for answers to both results in the voice of every persona, and write them to
+    /// the bank, replacing the one there was.
+    ///
+    /// The answers are requested through the OpenRouter API or the command backend, with the same
+    /// model, generation parameters and provider preferences the game would use.
+    Build {
+        /// The number of answers to each result for every persona.
+        #[arg(short = 'n', long, default_value = "10", value_name = "COUNT")]
+        count: NonZeroUsize,
+        /// A persona to build answers for, which can be given more than once; every persona by
+        /// default.
+        #[arg(long = "persona", value_name = "PERSONA")]
+        personas: Vec<String>,
+    },
+}
 
 /// Struct holding information relative to the command-line argument parser.
 #[derive(Parser)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "Every one of the bools is a command-line flag of its own."
+)]
 #[command(name = "randy", version, about, long_about = None)]
 #[command(next_line_help = true)]
 struct Cli {
     /// The OpenRouter API key to provide for the AI-based responses.
     ///
     /// This argument is only required if the environment variable OPENROUTER_API_KEY is not set
-    /// with the corresponding API key. Otherwise, you will have to specify this option.
-    #[arg(long)]
+    /// with the corresponding API key, and only when answering through the OpenRouter backend.
+    /// Without either of them, the game is played offline with canned cowboy lines. Typing the key
+    /// in the command-line leaves it in the shell history, which `--api-key-file` and
+    /// `--api-key-cmd` don't.
+    #[arg(long, hide_env_values = true)]
     #[arg(env = "OPENROUTER_API_KEY", value_name = "YOUR_API_KEY")]
-    api_key: String,
+    api_key: Option<String>,
+    /// The command to run through the shell for the API key, such as a password manager
+    /// invocation; the first line it prints is the key.
+    ///
+    /// This takes precedence over `--api-key`, and the command keeps the terminal for its own
+    /// prompts, if it has any.
+    #[arg(long, conflicts_with = "api_key_file")]
+    #[arg(env = "RANDY_API_KEY_CMD", value_name = "COMMAND")]
+    api_key_cmd: Option<String>,
+    /// The file whose first line is the API key, or `-` to read it from the standard input.
+    ///
+    /// This takes precedence over `--api-key`.
+    #[arg(long, env = "RANDY_API_KEY_FILE", value_name = "PATH")]
+    api_key_file: Option<PathBuf>,
+    /// The backend used to answer the result of every game; the OpenRouter API by default.
+    ///
+    /// The offline and mock backends need no API key nor network access. The command backend runs
+    /// the command given in `--backend-command` through the shell, with the chat completion request
+    /// body as JSON in its standard input and the result in the RANDY_RESULT environment variable.
+    #[arg(long, value_enum, default_value_t = BackendKind::OpenRouter)]
+    #[arg(env = "RANDY_BACKEND", value_name = "BACKEND")]
+    backend: BackendKind,
+    /// The command to run through the shell when using the command backend.
+    #[arg(long, required_if_eq("backend", "command"))]
+    #[arg(env = "RANDY_BACKEND_COMMAND", value_name = "COMMAND")]
+    backend_command: Option<String>,
+    /// The bank of answers generated ahead of time, to draw from with the bank backend and to
+    /// write to with `randy bank build`; `$XDG_DATA_HOME/randy/bank.json` by default.
+    #[arg(long, global = true, env = "RANDY_BANK", value_name = "PATH")]
+    bank: Option<PathBuf>,
+    /// The base URL of the OpenAI-compatible API to fetch answers and models from; the OpenRouter
+    /// API by default.
+    ///
+    /// This allows playing against local LLM servers such as llama.cpp, Ollama, vLLM or LM Studio,
+    /// e.g. `http://localhost:11434/v1` for Ollama. The API key is optional for any API other than
+    /// the default one.
+    #[arg(long, env = "RANDY_BASE_URL", value_name = "URL")]
+    base_url: Option<String>,
+    /// The number of candidate answers to ask for every round, from 1 to 8; only one by default.
+    ///
+    /// The candidate least like the answers the persona has given recently is the one shown, and
+    /// the answers shown are remembered between sessions in `$XDG_STATE_HOME/randy/history.toml`.
+    /// Every candidate costs as much as a whole answer, and answers are no longer typed out on
+    /// screen as they come in, since they can't be picked until every candidate is in.
+    #[arg(long, env = "RANDY_CANDIDATES", value_name = "COUNT")]
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=MAX_CANDIDATES))]
+    candidates: Option<u8>,
+    /// What to do instead of playing the game.
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The configuration file to read settings from; `$XDG_CONFIG_HOME/randy/config.toml` by
+    /// default.
+    ///
+    /// Settings given through the command-line or the environment take precedence over the ones in
+    /// the configuration file.
+    #[arg(long, env = "RANDY_CONFIG", value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// The models to fall back to, in order, whenever the one asked for is rate limited or down,
+    /// separated by commas.
+    ///
+    /// Each of them gets the very same request, and the result screen tells which model answered.
+    #[arg(long, env = "RANDY_FALLBACK_MODELS", value_name = "MODELS")]
+    #[arg(value_delimiter = ',')]
+    fallback_models: Vec<String>,
+    /// The maximum number of tokens in an answer; the default of the model otherwise.
+    #[arg(long, env = "RANDY_MAX_TOKENS", value_name = "TOKENS")]
+    max_tokens: Option<u32>,
     /// The model name to produce the response; Qwen3 32B by default.
     ///
     /// Models are processed by the string right below their public brand name in their respective
     /// OpenRouter model page. If you want to set it to anything other than the default free model,
-    /// you will have to either use that name in the command-line, the environment variable or
-    /// change it in the menu once in-game.
-    #[arg(short, long, requires = "api_key", value_parser = verify_model)]
+    /// you will have to either use that name in the command-line, the environment variable, the
+    /// configuration file or change it in the menu once in-game.
+    #[arg(short, long)]
     #[arg(env = "OPENROUTER_MODEL", value_name = "MODEL_NAME")]
     model: Option<String>,
+    /// Wait for the whole answer instead of typing it out on screen as it comes in.
+    #[arg(long, env = "RANDY_NO_STREAM")]
+    no_stream: bool,
+    /// Skip checking the API key against the account endpoints of the API on start.
+    ///
+    /// The check catches a rejected key before the game starts, and tells the credit left, the
+    /// rate limit and the daily quota of requests to free models, shown in the options menu and in
+    /// the status bar of the menus. APIs without these endpoints are left unchecked.
+    #[arg(long, env = "RANDY_NO_VERIFY_KEY")]
+    no_verify_key: bool,
+    /// Skip checking the model against the catalog of models available through the API on start.
+    ///
+    /// Models are only checked when answering through the API, never offline. The catalog is cached
+    /// for a day, so the check only reaches out to the API once the cache is stale. This flag is
+    /// meant for APIs that don't list all the models they serve.
+    #[arg(long, env = "RANDY_NO_VERIFY_MODEL")]
+    no_verify_model: bool,
+    /// Answer with canned cowboy lines, without any network access.
+    ///
+    /// This is the same as picking the offline backend. The game also falls back to these lines on
+    /// its own whenever there's no API key for the OpenRouter API, or a request to it fails.
+    #[arg(long, env = "RANDY_OFFLINE")]
+    offline: bool,
+    /// The persona narrating the game; the cowboy by default.
+    ///
+    /// The built-in personas are cowboy, pirate, noir and sports. More can be added through TOML
+    /// files in `$XDG_CONFIG_HOME/randy/personas`, named after the persona, e.g. `robot.toml`.
+    #[arg(short, long, env = "RANDY_PERSONA", value_name = "PERSONA")]
+    persona: Option<String>,
+    /// Request answers to both a correct and an incorrect guess while the guess is being typed in.
+    ///
+    /// The answer then shows up as soon as the guess is accepted, and the one that isn't used is
+    /// kept for a later round. Since the guess isn't known yet when requesting them, the prompt
+    /// templates are only used if they refer to no variable other than {score} and {streak}, and
+    /// the answers are told about nothing but the result otherwise, as with the default ones.
+    #[arg(long, env = "RANDY_PREFETCH")]
+    prefetch: bool,
+    /// Whether the OpenRouter API may fall back to other providers when the preferred ones fail;
+    /// true by default.
+    #[arg(long, env = "RANDY_PROVIDER_FALLBACKS", value_name = "BOOL")]
+    provider_fallbacks: Option<bool>,
+    /// The providers the OpenRouter API must never route requests to, separated by commas.
+    #[arg(long, env = "RANDY_PROVIDER_IGNORE", value_name = "PROVIDERS")]
+    #[arg(value_delimiter = ',')]
+    provider_ignore: Vec<String>,
+    /// The only providers the OpenRouter API may route requests to, separated by commas.
+    #[arg(long, env = "RANDY_PROVIDER_ONLY", value_name = "PROVIDERS")]
+    #[arg(value_delimiter = ',')]
+    provider_only: Vec<String>,
+    /// The providers the OpenRouter API tries first, in order, separated by commas.
+    #[arg(long, env = "RANDY_PROVIDER_ORDER", value_name = "PROVIDERS")]
+    #[arg(value_delimiter = ',')]
+    provider_order: Vec<String>,
+    /// How the OpenRouter API sorts providers when none of them is preferred: by price, throughput
+    /// or latency.
+    #[arg(long, env = "RANDY_PROVIDER_SORT", value_name = "SORT")]
+    provider_sort: Option<ProviderSort>,
+    /// The seed of the sampling, for answers that can be repeated with models that support it.
+    #[arg(long, env = "RANDY_SEED", value_name = "SEED")]
+    seed: Option<u64>,
+    /// The most the answers of a session may cost, in dollars, before switching to a free model.
+    ///
+    /// The cost is told by the API, or otherwise estimated from the prices of the model in the
+    /// catalog. Once the cap is reached, the game drops the fallback models that aren't free, and
+    /// switches to a free model, or answers offline if there's no free model to switch to.
+    #[arg(long, env = "RANDY_SPEND_CAP", value_name = "DOLLARS")]
+    #[arg(value_parser = parse_spend_cap)]
+    spend_cap: Option<f64>,
+    /// A sequence that stops the answer as soon as the model produces it; up to 4 of them.
+    ///
+    /// The environment variable takes them separated by commas.
+    #[arg(long, env = "RANDY_STOP", value_name = "SEQUENCE")]
+    #[arg(value_delimiter = ',')]
+    stop: Vec<String>,
+    /// Ask for answers as structured output, along with the mood they are delivered in, which the
+    /// result screen is colored and animated after.
+    ///
+    /// Models without support for structured output answer with plain text instead, shown as usual.
+    /// Structured answers aren't typed out on screen as they come in, since they can't be made
+    /// sense of until they are all in.
+    #[arg(long, env = "RANDY_STRUCTURED")]
+    structured: bool,
+    /// The temperature of the sampling, from 0 to 2; the higher, the wilder the answers.
+    #[arg(long, env = "RANDY_TEMPERATURE", value_name = "TEMPERATURE")]
+    temperature: Option<f64>,
+    /// The nucleus sampling threshold, above 0 and up to 1.
+    #[arg(long, env = "RANDY_TOP_P", value_name = "TOP_P")]
+    top_p: Option<f64>,
 }
 
-/// It makes up one of the fields the request to fetch models from the OpenRouter API requires. This
-/// structure doesn't support all of the mandatory and optional fields because the request is only
-/// interested in the model id.
-#[derive(Deserialize)]
-struct Data {
-    /// This field contains the name to be used on post requests in the model field for OpenRouter
-    /// POST API requests.
-    id: String,
+fn main() -> Result<()> {
+    let mut cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())?;
+    let generation = merge_generation(&mut cli, config.generation);
+    let base_url = cli
+        .base_url
+        .or(config.base_url)
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_owned())
+        .trim_end_matches('/')
+        .to_owned();
+    let model = cli.model.or(config.model);
+    let persona = cli.persona.or(config.persona);
+    let spend_cap = cli.spend_cap.or(config.spend_cap);
+    let bank = cli.bank.or(config.bank).or_else(Bank::default_path);
+    let candidates = cli.candidates.or(config.candidates).unwrap_or(1);
+    let fallback_models = if cli.fallback_models.is_empty() {
+        config.fallback_models
+    } else {
+        cli.fallback_models
+    };
+    let personas = Personas::load()?;
+
+    if !(1..=MAX_CANDIDATES).contains(&i64::from(candidates)) {
+        Cli::command()
+            .error(
+                ErrorKind::ValueValidation,
+                format!(
+                    "the number of candidates must be from 1 to {MAX_CANDIDATES}, not {candidates}"
+                ),
+            )
+            .exit();
+    }
+
+    if let Some(cap) = spend_cap {
+        verify_spend_cap(cap);
+    }
+
+    if let Some(ref persona) = persona {
+        verify_persona(persona, &personas);
+    }
+
+    let api_key_sources = [
+        (cli.api_key_file, cli.api_key_cmd),
+        (config.api_key_file, config.api_key_cmd),
+    ];
+    let backend = match (cli.backend, cli.backend_command) {
+        _ if cli.offline => Backend::Offline,
+        (BackendKind::Command, Some(command)) => Backend::Command(command),
+        (BackendKind::Command, None) => Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the command backend requires --backend-command",
+            )
+            .exit(),
+        (BackendKind::Bank, _) => Backend::Bank(load_bank(bank.as_deref())?),
+        (BackendKind::Mock, _) => Backend::Mock,
+        (BackendKind::Offline, _) => Backend::Offline,
+        (BackendKind::OpenRouter, _) => match load_api_key(cli.api_key, api_key_sources)? {
+            None if base_url == DEFAULT_BASE_URL => Backend::Offline,
+            api_key => Backend::OpenRouter(api_key),
+        },
+    };
+    let api_key = if let Backend::OpenRouter(Some(ref api_key)) = backend {
+        Some(api_key.clone())
+    } else {
+        None
+    };
+    let account = api_key
+        .as_ref()
+        .filter(|_| !cli.no_verify_key)
+        .and_then(|api_key| verify_key(api_key, &base_url));
+
+    let verify = !cli.no_verify_model && matches!(backend, Backend::OpenRouter(_));
+    for model in model.iter().chain(&fallback_models).filter(|_| verify) {
+        if let Err(err) = verify_model(model, &base_url) {
+            Cli::command().error(ErrorKind::ValueValidation, err).exit();
+        }
+    }
+
+    let settings = Settings {
+        account,
+        backend,
+        base_url,
+        candidates,
+        fallback_models,
+        generation,
+        model,
+        persona,
+        personas,
+        prefetch: cli.prefetch || config.prefetch.unwrap_or(false),
+        prompts: config.prompts,
+        spend_cap,
+        stream: !cli.no_stream,
+        structured: cli.structured || config.structured.unwrap_or(false),
+    };
+
+    let outcome = match cli.command {
+        Some(Command::Bank {
+            command: BankCommand::Build { count, personas },
+        }) => build_bank(&settings, count, &personas, bank.as_deref()),
+        None => randyrand::run(settings),
+    };
+
+    outcome.map_err(|err| match api_key {
+        Some(ref api_key) => anyhow!(api_key.redact(&format!("{err:#}"))),
+        None => err,
+    })
 }
 
-/// This structure contains the main form of the response returned by an OpenRouter API request for
-/// the list of all models available for use in the API.
-#[derive(Deserialize)]
-struct ModelResponse {
-    /// This field contains the only part of the response that the OpenRouter API returns on their
-    /// list all models GET request. It is a list of objects that is abstracted as another struct to
-    /// deserialize.
-    data: Vec<Data>,
+/// This function builds a bank with the given number of answers to each result for the given
+/// personas, reporting its progress on the way, and writes it to the given path.
+fn build_bank(
+    settings: &Settings,
+    count: NonZeroUsize,
+    personas: &[String],
+    path: Option<&Path>,
+) -> Result<()> {
+    let Some(path) = path else {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "there's no data directory to write the bank to; pass --bank",
+            )
+            .exit();
+    };
+    let term = Term::stderr();
+    let bank = Bank::build(settings, count.get(), personas, &mut |progress| {
+        drop(
+            term.clear_line()
+                .and_then(|()| term.write_str(&format!("Building the bank: {progress}"))),
+        );
+    })?;
+
+    term.clear_line()?;
+    bank.save(path)?;
+    term.write_line(&format!(
+        "Wrote {} answers by {} to {}.",
+        bank.size(),
+        bank.model(),
+        path.display()
+    ))?;
+
+    Ok(())
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// This function checks the given number of dollars is fit to cap the spending of a session with,
+/// which it isn't if it's negative or not a number at all.
+fn check_spend_cap(cap: f64) -> Result<f64, String> {
+    if cap.is_finite() && cap >= 0.0 {
+        Ok(cap)
+    } else {
+        Err(format!(
+            "the spend cap must be a number of dollars no less than 0, not {cap}"
+        ))
+    }
+}
+
+/// This function gets the API key from the first of its sources that is given: the file or the
+/// command given through the command-line or the environment, then the key itself, and then the
+/// file or the command in the configuration file.
+fn load_api_key(
+    api_key: Option<String>,
+    [given, configured]: [(Option<PathBuf>, Option<String>); 2],
+) -> Result<Option<ApiKey>> {
+    if let Some(api_key) = read_api_key(given)? {
+        return Ok(Some(api_key));
+    }
+    if let Some(api_key) = api_key {
+        return Ok(ApiKey::new(&api_key));
+    }
+
+    read_api_key(configured)
+}
+
+/// This function reads the bank the bank backend draws its answers from.
+fn load_bank(path: Option<&Path>) -> Result<Bank> {
+    let Some(path) = path else {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "there's no data directory to read the bank from; pass --bank",
+            )
+            .exit();
+    };
+
+    Bank::load(path).context("failed to load the bank of answers, built with `randy bank build`")
+}
+
+/// This function merges the generation parameters and provider preferences given through the
+/// command-line or the environment into the ones in the configuration file, taking them out of the
+/// former, and checks that the result is within the bounds the API takes.
+fn merge_generation(cli: &mut Cli, mut generation: Generation) -> Generation {
+    generation.max_tokens = cli.max_tokens.or(generation.max_tokens);
+    generation.seed = cli.seed.or(generation.seed);
+    generation.temperature = cli.temperature.or(generation.temperature);
+    generation.top_p = cli.top_p.or(generation.top_p);
+    if !cli.stop.is_empty() {
+        generation.stop = mem::take(&mut cli.stop);
+    }
+    generation.provider.allow_fallbacks = cli
+        .provider_fallbacks
+        .or(generation.provider.allow_fallbacks);
+    for (list, given) in [
+        (
+            &mut generation.provider.ignore,
+            mem::take(&mut cli.provider_ignore),
+        ),
+        (
+            &mut generation.provider.only,
+            mem::take(&mut cli.provider_only),
+        ),
+        (
+            &mut generation.provider.order,
+            mem::take(&mut cli.provider_order),
+        ),
+    ] {
+        if !given.is_empty() {
+            *list = given;
+        }
+    }
+    generation.provider.sort = cli.provider_sort.or(generation.provider.sort);
+
+    if let Err(err) = generation.validate() {
+        Cli::command().error(ErrorKind::ValueValidation, err).exit();
+    }
 
-    randyrand::run(cli.model, &cli.api_key)
+    generation
 }
 
-/// This function serves as a value parser for the command line argument parser in the `model`
-/// field. It basically makes a request to the OpenRouter API to retrieve the list of available
-/// models to use through their API and checks if the string passed by clap matches any one of the
-/// strings retrieved in the request.
-fn verify_model(string: &str) -> Result<String, String> {
-    let request = ureq::get("https://openrouter.ai/api/v1/models").call();
+/// This function parses the spend cap given through the command-line or the environment, turning
+/// down numbers that can't cap the spending of a session.
+fn parse_spend_cap(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .map_err(|err| format!("{err}"))
+        .and_then(check_spend_cap)
+}
 
-    match request {
-        Ok(response) => {
-            let response: ModelResponse =
-                response.into_body().read_json().expect("response failed");
-            let mut output =
-                String::from("The requested model could not be found with the OpenRouter API.");
+/// This function reads the API key from the given file, or else from the output of the given
+/// command, if either one is given.
+fn read_api_key((file, command): (Option<PathBuf>, Option<String>)) -> Result<Option<ApiKey>> {
+    match (file, command) {
+        (Some(file), _) => ApiKey::from_file(&file).map(Some),
+        (None, Some(command)) => ApiKey::from_command(&command).map(Some),
+        (None, None) => Ok(None),
+    }
+}
 
-            for Data { id } in response.data {
-                if id == string {
-                    string.clone_into(&mut output);
-                    return Ok(output);
-                }
-            }
+/// This function checks that the persona picked through the command-line, the environment or the
+/// configuration file is one of the available ones, and exits with the list of them if it isn't.
+fn verify_persona(persona: &str, personas: &Personas) {
+    if !personas.contains(persona) {
+        let available: Vec<_> = personas.ids().collect();
+        Cli::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!(
+                    "there's no persona called `{persona}`; the available ones are {}",
+                    available.join(", ")
+                ),
+            )
+            .exit();
+    }
+}
 
-            Err(output)
+/// This function checks the spend cap picked through the configuration file, since the ones given
+/// through the command-line or the environment are checked as they're parsed. If the spend cap
+/// can't cap the spending of a session, the program exits with the reason before the game starts.
+fn verify_spend_cap(cap: f64) {
+    if let Err(err) = check_spend_cap(cap) {
+        Cli::command().error(ErrorKind::ValueValidation, err).exit();
+    }
+}
+
+/// This function checks the API key against the account endpoints of the API, and returns the
+/// status of its account if the API tells. If the API rejects the key, the program exits with the
+/// reason before the game starts.
+fn verify_key(api_key: &ApiKey, base_url: &str) -> Option<Account> {
+    Account::check(base_url, api_key).unwrap_or_else(|err| {
+        Cli::command()
+            .error(ErrorKind::ValueValidation, format!("{err:#}"))
+            .exit()
+    })
+}
+
+/// This function checks the model picked through the command-line, the environment or the
+/// configuration file. It basically loads the catalog of models available through the API, from the
+/// cache if it's fresh enough, and checks if the given model matches any one of the models in it.
+/// If it doesn't, the closest matches are suggested instead.
+fn verify_model(model: &str, base_url: &str) -> Result<(), String> {
+    let catalog = ModelCatalog::load(base_url).map_err(|err| {
+        format!(
+            "There's been an error checking the requested model with the API at {base_url}: {err}"
+        )
+    })?;
+
+    if catalog.contains(model) {
+        return Ok(());
+    }
+
+    let mut message =
+        format!("The requested model `{model}` could not be found with the API at {base_url}.");
+    let suggestions = catalog.suggestions(model, 3);
+
+    if !suggestions.is_empty() {
+        message.push_str("\n\n  Did you mean one of these?\n");
+        for suggestion in suggestions {
+            message.push_str("\n    ");
+            message.push_str(suggestion);
         }
-        Err(_) => Err(
-            "There's been an error checking the requested model with the OpenRouter API."
-                .to_owned(),
-        ),
     }
+    message.push_str("\n\n  Pass --no-verify-model to use it anyway.");
+
+    Err(message)
 }
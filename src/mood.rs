@@ -0,0 +1,243 @@
+//! This module holds the mood the narrator delivers an answer in, when answers are asked for as
+//! structured output rather than as free text, along with how the result screen shows each mood.
+//!
+//! Models without support for structured output answer with free text instead, which is shown as
+//! it's always been, without any mood.
+
+use console::Color;
+use serde_json::{json, Value};
+
+use crate::sanitize::strip_reasoning;
+
+/// This constant holds the strongest intensity a mood can be delivered with.
+const MAX_INTENSITY: u8 = 5;
+
+/// This enumeration holds the moods the narrator can deliver an answer in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mood {
+    /// This variant is used for an answer delivered in anger, which shakes on screen.
+    Angry,
+    /// This variant is used for an answer delivered in excitement, which bounces on screen.
+    Excited,
+    /// This variant is used for an answer delivered in admiration, which pulses on screen.
+    Impressed,
+    /// This variant is used for an answer delivered without any particular feeling.
+    Neutral,
+    /// This variant is used for an answer delivered smugly.
+    Smug,
+    /// This variant is used for an answer delivered in sympathy.
+    Sympathetic,
+}
+
+impl Mood {
+    /// This constant holds every mood, in the order they are offered to the model in.
+    const ALL: [Self; 6] = [
+        Self::Neutral,
+        Self::Excited,
+        Self::Impressed,
+        Self::Smug,
+        Self::Sympathetic,
+        Self::Angry,
+    ];
+
+    /// This function returns the color the answer is shown in, if the mood has one.
+    const fn color(self) -> Option<Color> {
+        match self {
+            Self::Angry => Some(Color::Red),
+            Self::Excited => Some(Color::Yellow),
+            Self::Impressed => Some(Color::Green),
+            Self::Neutral => None,
+            Self::Smug => Some(Color::Magenta),
+            Self::Sympathetic => Some(Color::Cyan),
+        }
+    }
+
+    /// This function returns the name of the mood, as the model is asked to give it.
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Angry => "angry",
+            Self::Excited => "excited",
+            Self::Impressed => "impressed",
+            Self::Neutral => "neutral",
+            Self::Smug => "smug",
+            Self::Sympathetic => "sympathetic",
+        }
+    }
+}
+
+/// This structure holds the mood an answer is delivered in, along with how strongly, from 1 to 5.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Tone {
+    /// This field contains how strongly the mood is delivered, from 1 to 5.
+    intensity: u8,
+    /// This field contains the mood itself.
+    mood: Mood,
+}
+
+impl Tone {
+    /// This function returns the color the answer is shown in, if the mood has one.
+    pub(crate) const fn color(self) -> Option<Color> {
+        self.mood.color()
+    }
+
+    /// This function returns the frames of the animation the answer is shown with before settling
+    /// down, as the number of columns it's shifted right by, which may be negative, the number of
+    /// rows it's lifted by, and whether it's dimmed. The stronger the mood, the longer the
+    /// animation, and the wider the shaking.
+    pub(crate) fn frames(self) -> Vec<(isize, usize, bool)> {
+        let count = usize::from(self.intensity) * 2;
+        let reach = isize::from(self.intensity.div_ceil(2));
+
+        (0..count)
+            .filter_map(|frame| {
+                let even = frame % 2 == 0;
+                match self.mood {
+                    Mood::Angry => Some((if even { reach } else { -reach }, 0, false)),
+                    Mood::Excited => Some((0, usize::from(even), false)),
+                    Mood::Impressed => Some((0, 0, even)),
+                    Mood::Neutral | Mood::Smug | Mood::Sympathetic => None,
+                }
+            })
+            .collect()
+    }
+
+    /// This function returns whether the answer is shown in bold, which is the case for moods
+    /// delivered strongly.
+    pub(crate) const fn is_bold(self) -> bool {
+        self.intensity > MAX_INTENSITY / 2
+    }
+
+    /// This function returns the widest shift of any frame of the animation, in columns.
+    pub(crate) fn reach(self) -> usize {
+        self.frames()
+            .iter()
+            .map(|&(shift, _, _)| shift.unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// This function parses an answer given as structured output into the line itself and the tone it's
+/// delivered in, or returns nothing if the answer isn't structured, so that it can be taken as free
+/// text instead. Unknown moods are taken as neutral, and the intensity defaults to the middle one.
+///
+/// Reasoning models may think out loud before the structured output, so reasoning blocks are
+/// stripped before the answer is parsed.
+pub(crate) fn parse(answer: &str) -> Option<(String, Tone)> {
+    let answer = strip_reasoning(answer);
+    let answer = answer.trim();
+    let answer = answer
+        .strip_prefix("```json")
+        .or_else(|| answer.strip_prefix("```"))
+        .and_then(|answer| answer.strip_suffix("```"))
+        .unwrap_or(answer);
+    let value: Value = serde_json::from_str(answer.trim()).ok()?;
+    let line = value.get("line")?.as_str()?.to_owned();
+    let mood = value
+        .get("mood")
+        .and_then(Value::as_str)
+        .and_then(|name| {
+            Mood::ALL
+                .into_iter()
+                .find(|mood| mood.name().eq_ignore_ascii_case(name.trim()))
+        })
+        .unwrap_or(Mood::Neutral);
+    let intensity =
+        value
+            .get("intensity")
+            .and_then(Value::as_f64)
+            .map_or(MAX_INTENSITY / 2 + 1, |intensity| {
+                (1..=MAX_INTENSITY)
+                    .rev()
+                    .find(|&level| f64::from(level) <= intensity.round())
+                    .unwrap_or(1)
+            });
+
+    Some((line, Tone { intensity, mood }))
+}
+
+/// This function returns the format answers are asked for in when asked for as structured output,
+/// which is a JSON schema with the line, the mood and the intensity of the mood.
+pub(crate) fn response_format() -> Value {
+    let moods: Vec<_> = Mood::ALL.into_iter().map(Mood::name).collect();
+
+    json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": "answer",
+            "strict": true,
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "line": {
+                        "type": "string",
+                        "description": "The answer to the player, in your voice.",
+                    },
+                    "mood": {
+                        "type": "string",
+                        "enum": moods,
+                        "description": "The mood the answer is delivered in.",
+                    },
+                    "intensity": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_INTENSITY,
+                        "description": "How strongly the mood is delivered, from 1 to 5.",
+                    },
+                },
+                "required": ["line", "mood", "intensity"],
+                "additionalProperties": false,
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structured_answers_are_parsed() {
+        let Some((line, tone)) =
+            parse(r#"{"line": "Howdy, partner.", "mood": "smug", "intensity": 4}"#)
+        else {
+            panic!("the answer should have been parsed");
+        };
+
+        assert_eq!(line, "Howdy, partner.", "the line should be kept as is");
+        assert!(
+            tone == Tone {
+                intensity: 4,
+                mood: Mood::Smug,
+            },
+            "the mood and its intensity should be kept"
+        );
+    }
+
+    #[test]
+    fn reasoning_before_structured_answers_is_skipped() {
+        let answer = concat!(
+            "<think>The user guessed 4, so I'll be smug about it.</think>\n",
+            "```json\n",
+            r#"{"line": "Not even close, partner.", "mood": "Smug"}"#,
+            "\n```",
+        );
+        let Some((line, tone)) = parse(answer) else {
+            panic!("the answer should have been parsed past the reasoning");
+        };
+
+        assert_eq!(
+            line, "Not even close, partner.",
+            "the reasoning should be left out"
+        );
+        assert!(tone.mood == Mood::Smug, "the mood should be kept");
+    }
+
+    #[test]
+    fn free_text_is_not_parsed() {
+        assert!(
+            parse("<think>hmm</think>Howdy, partner.").is_none(),
+            "free text should be taken as it is"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! This module holds the locations of the files the game reads and writes, following the XDG base
+//! directory specification.
+
+use std::env;
+use std::path::PathBuf;
+
+/// This function returns the directory holding the files the game caches to save on network
+/// requests, which is `$XDG_CACHE_HOME/randy` or `~/.cache/randy` if the former is not set.
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    base_dir("XDG_CACHE_HOME", ".cache").map(|dir| dir.join("randy"))
+}
+
+/// This function returns the directory holding the configuration files of the game, which is
+/// `$XDG_CONFIG_HOME/randy` or `~/.config/randy` if the former is not set.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    base_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join("randy"))
+}
+
+/// This function returns the directory holding the data files the game generates, which is
+/// `$XDG_DATA_HOME/randy` or `~/.local/share/randy` if the former is not set.
+pub(crate) fn data_dir() -> Option<PathBuf> {
+    base_dir("XDG_DATA_HOME", ".local/share").map(|dir| dir.join("randy"))
+}
+
+/// This function returns the directory holding the files the game keeps between sessions, which
+/// is `$XDG_STATE_HOME/randy` or `~/.local/state/randy` if the former is not set.
+pub(crate) fn state_dir() -> Option<PathBuf> {
+    base_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join("randy"))
+}
+
+/// This function returns the base directory given by an XDG environment variable, falling back to
+/// the given directory under the home directory when the variable is not set or is not absolute.
+fn base_dir(variable: &str, fallback: &str) -> Option<PathBuf> {
+    env::var_os(variable)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+}
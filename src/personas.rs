@@ -0,0 +1,229 @@
+//! This module holds the personas narrating the game, each with its own voice for the system prompt
+//! of the LLM, a few example lines, and the lines to fall back to when answering offline.
+//!
+//! A few personas are built into the game, the cowboy being the default one. More can be added, and
+//! the built-in ones overridden, through TOML files in the `personas` directory of the
+//! configuration directory, each named after the identifier of the persona, e.g. `pirate.toml`.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::iter;
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+
+use crate::game::RandomResult;
+use crate::paths;
+
+/// This constant holds the identifier of the persona used unless another one is picked.
+pub(crate) const DEFAULT_PERSONA: &str = "cowboy";
+
+/// This constant holds the TOML definition of the built-in default persona.
+const DEFAULT_DEFINITION: &str = include_str!("personas/cowboy.toml");
+
+/// This constant holds the rest of the built-in personas, as identifiers along with their TOML
+/// definitions.
+const BUILT_IN: &[(&str, &str)] = &[
+    ("pirate", include_str!("personas/pirate.toml")),
+    ("noir", include_str!("personas/noir.toml")),
+    ("sports", include_str!("personas/sports.toml")),
+];
+
+/// This structure holds the lines a persona falls back to when answering offline.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FallbackLines {
+    /// This field contains the lines to answer correct guesses with.
+    correct: Vec<String>,
+    /// This field contains the lines to answer incorrect guesses with.
+    incorrect: Vec<String>,
+}
+
+/// This structure holds one of the personas narrating the game.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Persona {
+    /// This field contains a few lines in the voice of the persona, for the LLM to take after.
+    #[serde(default)]
+    examples: Vec<String>,
+    /// This field contains the lines to fall back to when answering offline. Personas without any
+    /// get the ones of the default persona.
+    #[serde(default)]
+    fallback: FallbackLines,
+    /// This field contains the identifier of the persona, which is the name of the file it comes
+    /// from.
+    #[serde(skip)]
+    id: String,
+    /// This field contains the name of the persona to show to the user.
+    name: String,
+    /// This field contains the description of the voice of the persona, for the system prompt of
+    /// the LLM.
+    system: String,
+}
+
+impl Persona {
+    /// This function returns a few lines in the voice of the persona.
+    pub(crate) fn examples(&self) -> &[String] {
+        &self.examples
+    }
+
+    /// This function returns the lines to fall back to for the given result of a game.
+    pub(crate) fn fallback(&self, result: RandomResult) -> &[String] {
+        match result {
+            RandomResult::Correct => &self.fallback.correct,
+            RandomResult::Incorrect => &self.fallback.incorrect,
+        }
+    }
+
+    /// This function returns the identifier of the persona.
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// This function gives the persona the lines to fall back to of another one, for whichever
+    /// result it has no lines of its own.
+    fn inherit_fallback(&mut self, other: &Self) {
+        if self.fallback.correct.is_empty() {
+            self.fallback.correct.clone_from(&other.fallback.correct);
+        }
+        if self.fallback.incorrect.is_empty() {
+            self.fallback
+                .incorrect
+                .clone_from(&other.fallback.incorrect);
+        }
+    }
+
+    /// This function returns the name of the persona to show to the user.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This function parses the TOML definition of the persona with the given identifier.
+    fn parse(id: &str, definition: &str) -> Result<Self, toml::de::Error> {
+        let mut persona: Self = toml::from_str(definition)?;
+        id.clone_into(&mut persona.id);
+
+        Ok(persona)
+    }
+
+    /// This function returns the description of the voice of the persona.
+    pub(crate) fn system(&self) -> &str {
+        &self.system
+    }
+}
+
+/// This structure holds all the personas that can narrate the game, the default one first.
+pub struct Personas {
+    /// This field contains the default persona.
+    default: Arc<Persona>,
+    /// This field contains the rest of the personas, none of them sharing an identifier, the
+    /// built-in ones first.
+    others: Vec<Arc<Persona>>,
+}
+
+impl Personas {
+    /// This function returns whether there's a persona with the given identifier.
+    #[must_use]
+    pub fn contains(&self, id: &str) -> bool {
+        self.list().any(|persona| persona.id == id)
+    }
+
+    /// This function returns the persona with the given identifier, or the default one if there's
+    /// no such persona.
+    pub(crate) fn get(&self, id: &str) -> Arc<Persona> {
+        Arc::clone(
+            self.list()
+                .find(|persona| persona.id == id)
+                .unwrap_or(&self.default),
+        )
+    }
+
+    /// This function returns the identifiers of all the personas, in the order they are listed in.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.list().map(|persona| persona.id.as_str())
+    }
+
+    /// This function returns all the personas, in the order they are listed in.
+    pub(crate) fn list(&self) -> impl Iterator<Item = &Arc<Persona>> {
+        iter::once(&self.default).chain(&self.others)
+    }
+
+    /// This function loads the built-in personas, along with the ones in the `personas` directory
+    /// of the configuration directory, which take the place of built-in ones with the same
+    /// identifier. A missing directory makes for the built-in personas only.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if the directory or any of the TOML files in it can't be
+    /// read, or if any of them isn't a valid persona definition.
+    pub fn load() -> Result<Self> {
+        let mut default = Persona::parse(DEFAULT_PERSONA, DEFAULT_DEFINITION)
+            .with_context(|| format!("failed to parse the built-in {DEFAULT_PERSONA} persona"))?;
+        let mut others = Vec::new();
+
+        for &(id, definition) in BUILT_IN {
+            let persona = Persona::parse(id, definition)
+                .with_context(|| format!("failed to parse the built-in {id} persona"))?;
+            others.push(persona);
+        }
+
+        for mut persona in load_dir()? {
+            if persona.id == DEFAULT_PERSONA {
+                persona.inherit_fallback(&default);
+                default = persona;
+            } else if let Some(other) = others.iter_mut().find(|other| other.id == persona.id) {
+                *other = persona;
+            } else {
+                others.push(persona);
+            }
+        }
+
+        for persona in &mut others {
+            persona.inherit_fallback(&default);
+        }
+
+        Ok(Self {
+            default: Arc::new(default),
+            others: others.into_iter().map(Arc::new).collect(),
+        })
+    }
+}
+
+/// This function loads the personas in the `personas` directory of the configuration directory, in
+/// the order of their file names.
+fn load_dir() -> Result<Vec<Persona>> {
+    let Some(dir) = paths::config_dir().map(|dir| dir.join("personas")) else {
+        return Ok(Vec::new());
+    };
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries.collect::<Result<Vec<_>, _>>(),
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => Err(err),
+    };
+    let mut paths: Vec<_> = entries
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .into_iter()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .collect();
+    paths.sort();
+
+    let mut personas = Vec::new();
+    for path in paths {
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let definition = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let persona = Persona::parse(id, &definition)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        personas.push(persona);
+    }
+
+    Ok(personas)
+}
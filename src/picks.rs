@@ -0,0 +1,89 @@
+//! This module holds the models the user pinned as favorites and the ones they used recently, kept
+//! between sessions in the state directory of the game.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// This constant holds the number of recently used models to remember.
+const RECENT_LIMIT: usize = 8;
+
+/// This structure holds the models the user pinned as favorites and the ones they used recently.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Picks {
+    /// This field contains the models pinned as favorites, in the order they were pinned in.
+    favorites: Vec<String>,
+    /// This field contains the models used recently, the most recent one first.
+    recent: Vec<String>,
+}
+
+impl Picks {
+    /// This function returns whether the given model is pinned as a favorite.
+    pub(crate) fn is_favorite(&self, id: &str) -> bool {
+        self.favorites.iter().any(|favorite| favorite == id)
+    }
+
+    /// This function returns whether the given model was used recently.
+    pub(crate) fn is_recent(&self, id: &str) -> bool {
+        self.recent.iter().any(|recent| recent == id)
+    }
+
+    /// This function reads the picks kept in the state directory. Since they are merely a
+    /// convenience, missing or unreadable picks make for empty ones.
+    pub(crate) fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// This function returns the position of the given model among the favorites and then the
+    /// recently used models, so that those can be pinned on top of the rest.
+    pub(crate) fn rank(&self, id: &str) -> Option<usize> {
+        self.favorites
+            .iter()
+            .chain(self.recent.iter())
+            .position(|pick| pick == id)
+    }
+
+    /// This function writes the picks to the state directory, creating it if needed.
+    pub(crate) fn save(&self) -> Result<()> {
+        let Some(path) = path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+
+        fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// This function pins the given model as a favorite, or unpins it if it already was one.
+    pub(crate) fn toggle_favorite(&mut self, id: &str) {
+        if self.is_favorite(id) {
+            self.favorites.retain(|favorite| favorite != id);
+        } else {
+            self.favorites.push(id.to_owned());
+        }
+    }
+
+    /// This function records the given model as the most recently used one.
+    pub(crate) fn use_model(&mut self, id: &str) {
+        self.recent.retain(|recent| recent != id);
+        self.recent.insert(0, id.to_owned());
+        self.recent.truncate(RECENT_LIMIT);
+    }
+}
+
+/// This function returns the location of the file the picks are kept in.
+fn path() -> Option<PathBuf> {
+    paths::state_dir().map(|dir| dir.join("models.toml"))
+}
@@ -0,0 +1,106 @@
+//! This module holds the answers requested ahead of time for both results of a round, so that the
+//! answer to the one the player gets is on its way, if not already in, by the time they accept
+//! their guess.
+//!
+//! Since the guess isn't known yet when they are requested, they are requested with messages made
+//! from the templates of the round only when these refer to nothing but the score and the streak,
+//! which are known for either result, and with messages telling about nothing but the result
+//! otherwise. The answer for the result that didn't come up is kept for a later round, unless the
+//! message it would be requested with by then is a different one.
+
+use anyhow::Result;
+
+use crate::chat::Transcript;
+use crate::game::{RandomResult, Round, Session};
+use crate::generation::Generation;
+use crate::worker::{Job, Ticket, Worker};
+
+/// This structure holds the tickets of the answers requested ahead of time for each result, along
+/// with what they were requested with, so that they are thrown away once any of it changes.
+#[derive(Default)]
+pub(crate) struct Prefetch {
+    /// This field contains the ticket of the answer to a correct guess, along with the message it
+    /// was requested with, if it has been requested.
+    correct: Option<(Ticket, String)>,
+    /// This field contains the ticket of the answer to an incorrect guess, along with the message
+    /// it was requested with, if it has been requested.
+    incorrect: Option<(Ticket, String)>,
+    /// This field contains the model, the identifier of the persona and the generation parameters
+    /// the answers were requested with.
+    key: Option<(String, String, Generation)>,
+}
+
+impl Prefetch {
+    /// This function cancels the answers requested ahead of time that haven't been taken yet.
+    pub(crate) fn clear(&mut self, worker: &mut Worker) {
+        for (ticket, _) in [self.correct.take(), self.incorrect.take()]
+            .into_iter()
+            .flatten()
+        {
+            worker.cancel(ticket);
+        }
+        self.key = None;
+    }
+
+    /// This function requests an answer ahead of time for each result that doesn't have one on its
+    /// way already, with the model, persona and generation parameters of the session, and the given
+    /// transcript of the session so far. The messages telling about the results are made from the
+    /// templates of the session if they can be, for a round played after the given score and the
+    /// result and streak of the last round, if any. Answers requested with anything else are thrown
+    /// away first.
+    pub(crate) fn fill(
+        &mut self,
+        session: &mut Session<'_>,
+        transcript: &Transcript,
+        score: u32,
+        last: Option<(RandomResult, u32)>,
+    ) -> Result<()> {
+        let key = (
+            session.model().to_owned(),
+            session.persona().id().to_owned(),
+            session.generation().clone(),
+        );
+        if self.key.as_ref() != Some(&key) {
+            self.clear(session.worker());
+            self.key = Some(key);
+        }
+
+        let ahead = session.prompts().is_known_ahead();
+        for (result, slot) in [
+            (RandomResult::Correct, &mut self.correct),
+            (RandomResult::Incorrect, &mut self.incorrect),
+        ] {
+            let message = if ahead {
+                // The templates refer to neither the guess, nor the number to guess, nor the range,
+                // so they are left out.
+                session
+                    .prompts()
+                    .render(&Round::new(result, 0, 0, (0, 0), score, last))
+            } else {
+                result.message().to_owned()
+            };
+
+            if let Some((ticket, _)) = slot.take_if(|&mut (_, ref asked)| *asked != message) {
+                session.worker().cancel(ticket);
+            }
+            if slot.is_none() {
+                let request = session.request(transcript, &message).unstreamed();
+                let ticket = session
+                    .worker()
+                    .submit(Job::Complete(Box::new(request), result))?;
+                *slot = Some((ticket, message));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// This function takes the ticket of the answer requested ahead of time for the given result,
+    /// along with the message it was requested with, if there's one.
+    pub(crate) const fn take(&mut self, result: RandomResult) -> Option<(Ticket, String)> {
+        match result {
+            RandomResult::Correct => self.correct.take(),
+            RandomResult::Incorrect => self.incorrect.take(),
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! This module holds the post-processing every answer from an LLM goes through before it's shown,
+//! since models don't always stick to the system prompt.
+//!
+//! Answers are stripped of reasoning blocks, markdown, emoji and wrapping quotes, have their
+//! whitespace collapsed and are cut down to a maximum length. Answers with nothing left after that
+//! are off-format, and it's up to the caller to ask again or fall back to something else.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::backend::Piece;
+
+/// This constant holds the maximum number of characters of an answer.
+const MAX_CHARS: usize = 280;
+
+/// This constant holds the tags reasoning models wrap their reasoning in, in lowercase.
+const REASONING_TAGS: &[&str] = &["think", "thinking", "reasoning"];
+
+/// This constant holds the pairs of quotes an answer may come wrapped in.
+const QUOTES: &[(char, char)] = &[
+    ('"', '"'),
+    ('\'', '\''),
+    ('\u{201c}', '\u{201d}'),
+    ('\u{2018}', '\u{2019}'),
+    ('\u{ab}', '\u{bb}'),
+];
+
+/// This static variable holds the expression matching markdown links and images, capturing their
+/// text.
+static LINK: LazyLock<Option<Regex>> =
+    LazyLock::new(|| Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").ok());
+
+/// This static variable holds the expression matching the markers at the start of markdown
+/// headings, quotes and list items.
+static LINE_MARKER: LazyLock<Option<Regex>> =
+    LazyLock::new(|| Regex::new(r"(?m)^[ \t]*(?:#{1,6}|>|[-*+]|\d+[.)])[ \t]+").ok());
+
+/// This static variable holds the expression matching text emphasized with underscores, capturing
+/// the text.
+static UNDERSCORES: LazyLock<Option<Regex>> =
+    LazyLock::new(|| Regex::new(r"\b_{1,2}([^_]+?)_{1,2}\b").ok());
+
+/// This structure filters the pieces of a streamed answer as they come in, so that reasoning blocks
+/// and emoji never make it to the screen. The rest of the clean-up only happens once the whole
+/// answer is in.
+#[derive(Default)]
+pub(crate) struct StreamFilter {
+    /// This field contains the text handed over so far.
+    emitted: String,
+    /// This field contains the answer as it came in so far.
+    raw: String,
+}
+
+impl StreamFilter {
+    /// This function takes in the next piece of the answer, and hands over to the given sink the
+    /// text that comes out of it, if any. Text is held back for as long as it might be the start
+    /// of a reasoning tag. If the text handed over so far turns out to have been reasoning after
+    /// all, as told by a closing tag without an opening one, the sink is told to start over, and
+    /// the answer is handed over again from its new start.
+    pub(crate) fn push(&mut self, piece: &str, sink: &mut dyn FnMut(Piece<'_>)) {
+        self.raw.push_str(piece);
+
+        let mut visible = strip_reasoning(&self.raw);
+        if let Some(start) = visible.rfind('<') {
+            let rest = visible
+                .get(start..)
+                .unwrap_or_default()
+                .to_ascii_lowercase();
+            let is_tag_start = REASONING_TAGS.iter().any(|tag| {
+                format!("<{tag}>").starts_with(&rest) || format!("</{tag}>").starts_with(&rest)
+            });
+            if is_tag_start {
+                visible.truncate(start);
+            }
+        }
+        let visible = strip_symbols(&visible);
+
+        let fresh = if let Some(fresh) = visible.strip_prefix(self.emitted.as_str()) {
+            fresh.to_owned()
+        } else {
+            self.emitted.clear();
+            sink(Piece::Restart);
+            visible
+        };
+        if !fresh.is_empty() {
+            self.emitted.push_str(&fresh);
+            sink(Piece::Text(&fresh));
+        }
+    }
+}
+
+/// This function cleans up an answer, and returns it unless there's nothing left of it, which makes
+/// it off-format.
+pub(crate) fn sanitize(answer: &str) -> Option<String> {
+    let text = strip_reasoning(answer);
+    let text = strip_markdown(&text);
+    let text = strip_symbols(&text);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = strip_quotes(&text);
+    let text = truncate(text);
+
+    text.chars().any(char::is_alphanumeric).then_some(text)
+}
+
+/// This function returns whether a character is an emoji, or one of the invisible characters that
+/// glue emoji together.
+const fn is_emoji(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{1f000}'..='\u{1faff}'
+            | '\u{2600}'..='\u{27bf}'
+            | '\u{2b00}'..='\u{2bff}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{200d}'
+            | '\u{20e3}'
+            | '\u{e0020}'..='\u{e007f}'
+    )
+}
+
+/// This function removes the markdown emphasis, code and link syntax from a text, along with the
+/// markers of headings, quotes and list items.
+fn strip_markdown(text: &str) -> String {
+    let mut text = text.to_owned();
+
+    for (regex, replacement) in [(&*LINK, "$1"), (&*LINE_MARKER, ""), (&*UNDERSCORES, "$1")] {
+        if let Some(ref regex) = *regex {
+            text = regex.replace_all(&text, replacement).into_owned();
+        }
+    }
+
+    text.replace("~~", "")
+}
+
+/// This function removes the wrapping quotes of a text, if it comes wrapped in a pair of them.
+fn strip_quotes(text: &str) -> String {
+    let mut text = text.trim();
+
+    for &(open, close) in QUOTES {
+        if let Some(inner) = text
+            .strip_prefix(open)
+            .and_then(|inner| inner.strip_suffix(close))
+        {
+            if !inner.contains(open) && !inner.contains(close) {
+                text = inner.trim();
+                break;
+            }
+        }
+    }
+
+    text.to_owned()
+}
+
+/// This function removes the reasoning blocks from a text. A block left open hides the rest of the
+/// text, and a closing tag without an opening one hides all the text before it, since some models
+/// leave the opening tag out.
+pub(crate) fn strip_reasoning(text: &str) -> String {
+    let lowered = text.to_ascii_lowercase();
+    let mut output = String::new();
+    let mut position = REASONING_TAGS
+        .iter()
+        .filter_map(|tag| {
+            let close = format!("</{tag}>");
+            let end = lowered.find(&close)?;
+            let open = format!("<{tag}>");
+            (!lowered.get(..end)?.contains(&open)).then_some(end + close.len())
+        })
+        .max()
+        .unwrap_or(0);
+
+    while let Some((start, tag)) = REASONING_TAGS
+        .iter()
+        .filter_map(|tag| {
+            let start = lowered.get(position..)?.find(&format!("<{tag}>"))?;
+            Some((position + start, tag))
+        })
+        .min_by_key(|&(start, _)| start)
+    {
+        output.push_str(text.get(position..start).unwrap_or_default());
+
+        let close = format!("</{tag}>");
+        match lowered.get(start..).and_then(|rest| rest.find(&close)) {
+            Some(end) => position = start + end + close.len(),
+            None => return output,
+        }
+    }
+
+    output.push_str(text.get(position..).unwrap_or_default());
+    output
+}
+
+/// This function removes emoji, along with the asterisks and backticks of markdown emphasis and
+/// code, from a text. It works character by character, so it's safe to use on partial answers.
+fn strip_symbols(text: &str) -> String {
+    text.chars()
+        .filter(|&ch| !is_emoji(ch) && ch != '*' && ch != '`')
+        .collect()
+}
+
+/// This function cuts a text down to the maximum length of an answer, at the end of a sentence if
+/// there's one far enough into it, or otherwise at the end of a word followed by an ellipsis.
+fn truncate(text: String) -> String {
+    if text.chars().count() <= MAX_CHARS {
+        return text;
+    }
+
+    let cut: String = text.chars().take(MAX_CHARS).collect();
+    let sentence = cut
+        .rfind(['.', '!', '?'])
+        .filter(|&end| end >= cut.len() / 2);
+
+    if let Some(end) = sentence {
+        return cut.get(..=end).unwrap_or_default().to_owned();
+    }
+
+    let word = cut.rfind(char::is_whitespace).unwrap_or(cut.len());
+    format!("{}...", cut.get(..word).unwrap_or_default().trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// This function hands the given pieces over to a stream filter one at a time, and returns
+    /// what the screen would show in the end, starting over whenever told to.
+    fn filtered(pieces: &[&str]) -> String {
+        let mut filter = StreamFilter::default();
+        let mut shown = String::new();
+        for piece in pieces {
+            filter.push(piece, &mut |piece| match piece {
+                Piece::Restart => shown.clear(),
+                Piece::Text(text) => shown.push_str(text),
+            });
+        }
+
+        shown
+    }
+
+    #[test]
+    fn reasoning_blocks_are_stripped() {
+        assert_eq!(
+            strip_reasoning("<think>hmm</think>Howdy <THINKING>so</THINKING>partner"),
+            "Howdy partner",
+            "closed blocks should be removed whatever the case of their tags"
+        );
+        assert_eq!(
+            strip_reasoning("Howdy <reasoning>and then"),
+            "Howdy ",
+            "a block left open should hide the rest of the text"
+        );
+        assert_eq!(
+            strip_reasoning("the user guessed 4</think>Howdy"),
+            "Howdy",
+            "a closing tag without an opening one should hide the text before it"
+        );
+        assert_eq!(
+            strip_reasoning("Howdy </b> partner"),
+            "Howdy </b> partner",
+            "tags other than reasoning ones should be left alone"
+        );
+    }
+
+    #[test]
+    fn wrapping_quotes_are_stripped() {
+        assert_eq!(strip_quotes("\"Howdy\""), "Howdy", "straight quotes");
+        assert_eq!(
+            strip_quotes("\u{201c} Howdy \u{201d}"),
+            "Howdy",
+            "curly quotes, along with the whitespace inside them"
+        );
+        assert_eq!(
+            strip_quotes("\"Howdy,\" he said, \"partner\""),
+            "\"Howdy,\" he said, \"partner\"",
+            "quotes that don't wrap the whole text should be left alone"
+        );
+        assert_eq!(strip_quotes("'Howdy\""), "'Howdy\"", "unmatched quotes");
+    }
+
+    #[test]
+    fn long_answers_are_truncated() {
+        assert_eq!(truncate("Howdy.".to_owned()), "Howdy.", "short answers");
+
+        let sentence = format!("{} Yeehaw {}", "Howdy partner.".repeat(15), "a".repeat(200));
+        assert_eq!(
+            truncate(sentence),
+            "Howdy partner.".repeat(15),
+            "long answers should end at the last sentence far enough into them"
+        );
+
+        let words = "howdy ".repeat(60);
+        let truncated = truncate(words);
+        assert!(
+            truncated.ends_with("howdy..."),
+            "long answers without sentences should end at a word: {truncated}"
+        );
+        assert!(
+            truncated.chars().count() <= MAX_CHARS + 3,
+            "truncated answers should fit in the maximum length: {truncated}"
+        );
+    }
+
+    #[test]
+    fn answers_are_sanitized() {
+        assert_eq!(
+            sanitize("<think>ok</think> \"**Howdy**, [partner](https://example.com)!\" \u{1f920}")
+                .as_deref(),
+            Some("Howdy, partner!"),
+            "reasoning, markdown, emoji and quotes should be stripped"
+        );
+        assert_eq!(
+            sanitize("<think>no answer at all</think> \u{1f920}"),
+            None,
+            "answers with nothing left are off-format"
+        );
+    }
+
+    #[test]
+    fn streams_hold_back_reasoning() {
+        assert_eq!(
+            filtered(&["Howdy <thi", "nk>hmm</th", "ink> part", "ner \u{1f920}"]),
+            "Howdy  partner ",
+            "reasoning split across pieces should never be handed over"
+        );
+    }
+
+    #[test]
+    fn streams_start_over_after_a_late_closing_tag() {
+        assert_eq!(
+            filtered(&["the user guessed 4", "</think>Howdy", " partner"]),
+            "Howdy partner",
+            "the reasoning handed over before the closing tag should be taken back"
+        );
+    }
+
+    #[test]
+    fn streams_only_hold_back_what_may_be_a_tag() {
+        assert_eq!(
+            filtered(&["Howdy <", "3 partner"]),
+            "Howdy <3 partner",
+            "a stray angle bracket should be handed over once it can't start a tag"
+        );
+        assert_eq!(
+            filtered(&["Howdy </thi"]),
+            "Howdy ",
+            "what may still be a closing tag should be held back"
+        );
+    }
+}
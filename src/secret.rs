@@ -0,0 +1,101 @@
+//! This module holds the API key, along with the ways of getting it other than typing it in the
+//! command-line, which leaks it into the shell history.
+//!
+//! The key is never shown: it's left out of its debug representation, and taken out of any message
+//! that might echo it back, such as the errors of the API.
+
+use core::fmt::{self, Debug, Formatter};
+use std::fs;
+use std::io::{self, Read as _};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+
+/// This constant holds what the API key is replaced with wherever it would be shown.
+const REDACTED: &str = "[redacted]";
+
+/// This structure holds the API key sent along every request to the API.
+#[derive(Clone)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// This function returns the API key itself, to be sent along a request and nowhere else.
+    pub(crate) fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// This function reads the API key from the first line of the output of the given command, run
+    /// through the shell, such as a password manager invocation. The command keeps the terminal for
+    /// its own prompts, if it has any.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if the command can't be run, if it fails, or if it prints
+    /// nothing.
+    pub fn from_command(command: &str) -> Result<Self> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .output()
+            .with_context(|| format!("failed to run `{command}` for the API key"))?;
+
+        if !output.status.success() {
+            bail!(
+                "`{command}` failed to print the API key ({})",
+                output.status
+            );
+        }
+
+        Self::parse(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| anyhow!("`{command}` printed no API key"))
+    }
+
+    /// This function reads the API key from the first line of the file at the given path, or of the
+    /// standard input if the path is `-`.
+    ///
+    /// # Errors
+    ///
+    /// The function may return an error if the file can't be read, or if it's empty.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = if path == Path::new("-") {
+            let mut contents = String::new();
+            let _ = io::stdin()
+                .read_to_string(&mut contents)
+                .context("failed to read the API key from the standard input")?;
+            contents
+        } else {
+            fs::read_to_string(path)
+                .with_context(|| format!("failed to read the API key from {}", path.display()))?
+        };
+
+        Self::parse(&contents).ok_or_else(|| anyhow!("there's no API key in {}", path.display()))
+    }
+
+    /// This function takes the API key as given, trimmed, unless it's empty.
+    #[must_use]
+    pub fn new(key: &str) -> Option<Self> {
+        Some(key.trim())
+            .filter(|key| !key.is_empty())
+            .map(|key| Self(key.to_owned()))
+    }
+
+    /// This function takes the API key from the first line with anything on it.
+    fn parse(contents: &str) -> Option<Self> {
+        contents.lines().find_map(Self::new)
+    }
+
+    /// This function returns the given message with every occurrence of the API key taken out.
+    #[must_use]
+    pub fn redact(&self, message: &str) -> String {
+        message.replace(&self.0, REDACTED)
+    }
+}
+
+impl Debug for ApiKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ApiKey").field(&REDACTED).finish()
+    }
+}
@@ -0,0 +1,73 @@
+//! This module holds the settings the game is started with.
+
+use crate::account::Account;
+use crate::bank::Bank;
+use crate::generation::Generation;
+use crate::personas::Personas;
+use crate::secret::ApiKey;
+use crate::template::Prompts;
+
+/// This constant holds the base URL of the OpenRouter API, used unless another OpenAI-compatible
+/// API is configured.
+pub const DEFAULT_BASE_URL: &str = "https://openrouter.ai/api/v1";
+
+/// This constant holds the model used for the answers unless another one is picked, which is also
+/// the first choice to switch over to once the spend cap is reached.
+pub const DEFAULT_MODEL: &str = "qwen/qwen3-32b:free";
+
+/// This enumeration holds the backends the game can answer the result of a game with.
+pub enum Backend {
+    /// This variant is used to answer with lines drawn from the given bank of answers generated
+    /// ahead of time, without any network access.
+    Bank(Bank),
+    /// This variant is used to run the given command through the shell to produce an answer.
+    Command(String),
+    /// This variant is used to answer with the same message an LLM would otherwise receive.
+    Mock,
+    /// This variant is used to answer with canned lines, without any network access.
+    Offline,
+    /// This variant is used to fetch answers through the OpenRouter API, or any other
+    /// OpenAI-compatible API, with the given API key if the API requires one.
+    OpenRouter(Option<ApiKey>),
+}
+
+/// This structure holds all the settings the game can be configured with before it starts.
+pub struct Settings {
+    /// This field contains the status of the account behind the API key, as checked on start, if
+    /// the API told.
+    pub account: Option<Account>,
+    /// This field contains the backend used to answer the result of every game.
+    pub backend: Backend,
+    /// This field contains the base URL of the API to fetch answers and models from, without the
+    /// trailing slash.
+    pub base_url: String,
+    /// This field contains the number of candidate answers to ask for every round, of which the
+    /// one least like the answers shown recently is kept.
+    pub candidates: u8,
+    /// This field contains the models to fall back to, in order, whenever the one asked for is
+    /// rate limited or down.
+    pub fallback_models: Vec<String>,
+    /// This field contains the parameters the LLM generates its answers with, along with the
+    /// preferences for the providers requests are routed to.
+    pub generation: Generation,
+    /// This field contains the model to use for the answers, if other than the default one.
+    pub model: Option<String>,
+    /// This field contains the identifier of the persona narrating the game, if other than the
+    /// default one.
+    pub persona: Option<String>,
+    /// This field contains all the personas that can narrate the game.
+    pub personas: Personas,
+    /// This field contains whether answers to both results of a round are requested while the
+    /// player is still typing in their guess.
+    pub prefetch: bool,
+    /// This field contains the templates of the message telling the LLM about every round.
+    pub prompts: Prompts,
+    /// This field contains the most the answers of a session may cost, in dollars, before the game
+    /// switches to a free model, if there's a cap.
+    pub spend_cap: Option<f64>,
+    /// This field contains whether answers are streamed and typed out on screen as they come in.
+    pub stream: bool,
+    /// This field contains whether answers are asked for as structured output, along with the mood
+    /// they are delivered in, which the result screen is styled after.
+    pub structured: bool,
+}
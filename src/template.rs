@@ -0,0 +1,98 @@
+//! This module holds the templates the message telling the LLM about the result of a round is made
+//! from, so that the answer can refer to what happened in it.
+//!
+//! Templates are plain text with variables between braces, e.g. `{guess}`, which get replaced by
+//! their value for the round. The variables are the following:
+//!
+//! - `{guess}`: the number the player guessed.
+//! - `{target}`: the number that was to be guessed.
+//! - `{range_start}` and `{range_end}`: the bounds of the range the number was picked from.
+//! - `{distance}`: how far off the guess was from the number.
+//! - `{score}`: the number of rounds the player got right so far, this one included.
+//! - `{streak}`: the number of rounds in a row with the same result, this one included.
+//!
+//! Unknown variables are left as they are.
+//!
+//! Only `{score}` and `{streak}` are known before the guess is in, so the answers requested ahead
+//! of time are only asked for with templates that refer to no other variable.
+
+use serde::Deserialize;
+
+use crate::game::{RandomResult, Round};
+
+/// This constant holds the names of the variables that are only known once the guess is in.
+const PER_GUESS: [&str; 5] = ["distance", "guess", "range_end", "range_start", "target"];
+
+/// This structure holds the templates of the message telling the LLM about the result of a round,
+/// one for each result.
+///
+/// Both of them can be overridden through the `[prompts]` table of the configuration file.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prompts {
+    /// This field contains the template used when the guess is correct.
+    pub correct: String,
+    /// This field contains the template used when the guess is incorrect.
+    pub incorrect: String,
+}
+
+impl Default for Prompts {
+    fn default() -> Self {
+        Self {
+            correct:
+                "Correct. The player guessed {guess}, and the number between {range_start} and \
+                {range_end} was indeed {target}. Their score is now {score}, and they got \
+                {streak} right in a row."
+                    .to_owned(),
+            incorrect: "Incorrect. The player guessed {guess}, but the number between \
+                {range_start} and {range_end} was {target}, so they missed by {distance}. Their \
+                score is {score}, and they got {streak} wrong in a row."
+                .to_owned(),
+        }
+    }
+}
+
+impl Prompts {
+    /// This function returns whether both templates can be rendered before the guess is in, which
+    /// is the case unless they refer to any of the variables only known once it is.
+    pub(crate) fn is_known_ahead(&self) -> bool {
+        [&self.correct, &self.incorrect]
+            .into_iter()
+            .all(|template| {
+                PER_GUESS
+                    .iter()
+                    .all(|name| !template.contains(&format!("{{{name}}}")))
+            })
+    }
+
+    /// This function returns the message telling the LLM about the result of the given round, out
+    /// of the template for its result.
+    pub(crate) fn render(&self, round: &Round) -> String {
+        let template = match round.result() {
+            RandomResult::Correct => &self.correct,
+            RandomResult::Incorrect => &self.incorrect,
+        };
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template.as_str();
+
+        while let Some(start) = rest.find('{') {
+            let (before, after) = rest.split_at(start);
+            message.push_str(before);
+
+            let value = after
+                .find('}')
+                .and_then(|end| Some((after.get(1..end)?, end)))
+                .and_then(|(name, end)| Some((round.variable(name)?, end)));
+            if let Some((value, end)) = value {
+                message.push_str(&value);
+                rest = after.get(end + 1..).unwrap_or_default();
+            } else {
+                message.push('{');
+                rest = after.get(1..).unwrap_or_default();
+            }
+        }
+        message.push_str(rest);
+
+        message
+    }
+}
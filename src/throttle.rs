@@ -0,0 +1,290 @@
+//! This module keeps track of how many more requests the API takes before rate limiting them, so
+//! that requests are held back until they would go through rather than sent only to be turned
+//! down.
+//!
+//! The quota is told by the rate limit headers of every response, and tracked locally for the
+//! models that cost nothing, which the OpenRouter API only takes so many requests to every minute
+//! and every day. The requests made to them on the current day are kept between sessions in the
+//! state directory of the game.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+use ureq::http::HeaderMap;
+
+use crate::backend::error::CompletionError;
+use crate::paths;
+use crate::worker::doze;
+
+/// This constant holds the number of seconds in a day, after which the daily quota starts over.
+const DAY: u64 = 24 * 60 * 60;
+
+/// This constant holds the number of requests to free models the API takes every minute.
+const FREE_PER_MINUTE: usize = 20;
+
+/// This constant holds the longest a request is held back for; requests that would have to wait
+/// for longer are given up on as rate limited right away.
+const MAX_WAIT: Duration = Duration::from_secs(120);
+
+/// This constant holds the time requests to free models are counted over for the quota of every
+/// minute.
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// This structure holds the number of requests to free models made on a given day.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Daily {
+    /// This field contains the day the requests were made on, in days since the Unix epoch.
+    day: u64,
+    /// This field contains the number of requests made on that day.
+    requests: u32,
+}
+
+impl Daily {
+    /// This function reads the requests of the day kept in the state directory. Since they are
+    /// merely a way of avoiding requests bound to fail, missing or unreadable ones make for none.
+    fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// This function writes the requests of the day to the state directory, creating it if needed.
+    fn save(&self) -> Result<()> {
+        let Some(path) = path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+
+        fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// This function returns the number of requests made today, starting the count over if the
+    /// last ones were made on another day.
+    fn today(&mut self, now: SystemTime) -> &mut u32 {
+        let today = since_epoch(now).as_secs() / DAY;
+        if self.day != today {
+            self.day = today;
+            self.requests = 0;
+        }
+
+        &mut self.requests
+    }
+}
+
+/// This structure holds what's known of the quota of the API.
+struct Quota {
+    /// This field contains the time the API asked to wait until after turning down a request for
+    /// having sent too many of them, if it did.
+    blocked: Option<Instant>,
+    /// This field contains the requests to free models made today.
+    daily: Daily,
+    /// This field contains the number of requests to free models the account may make a day, if
+    /// known.
+    daily_limit: Option<u32>,
+    /// This field contains the time the request being held back is let through at, if one is.
+    held: Option<Instant>,
+    /// This field contains the times the requests to free models made in the last minute were
+    /// sent at, the oldest first.
+    recent: VecDeque<Instant>,
+    /// This field contains the number of requests left and the time they are reset at, as told by
+    /// the rate limit headers of the last response that had them.
+    window: Option<(u64, Instant)>,
+}
+
+impl Quota {
+    /// This function returns how long a request to the given model has to wait for before going
+    /// through, if it has to wait at all.
+    fn delay(&mut self, model: &str) -> Option<Duration> {
+        let now = Instant::now();
+        let free = is_free(model);
+
+        while self
+            .recent
+            .front()
+            .is_some_and(|&sent| now.duration_since(sent) >= MINUTE)
+        {
+            let _ = self.recent.pop_front();
+        }
+
+        let blocked = self
+            .blocked
+            .map(|until| until.saturating_duration_since(now));
+        let window = self
+            .window
+            .filter(|&(remaining, _)| remaining == 0)
+            .map(|(_, reset)| reset.saturating_duration_since(now));
+        let minute = self
+            .recent
+            .len()
+            .checked_sub(FREE_PER_MINUTE)
+            .filter(|_| free)
+            .and_then(|oldest| self.recent.get(oldest))
+            .map(|&oldest| (oldest + MINUTE).saturating_duration_since(now));
+        let today = SystemTime::now();
+        let requests = *self.daily.today(today);
+        let daily = self
+            .daily_limit
+            .filter(|&limit| free && requests >= limit)
+            .map(|_| Duration::from_secs(DAY - since_epoch(today).as_secs() % DAY));
+
+        [blocked, window, minute, daily]
+            .into_iter()
+            .flatten()
+            .max()
+            .filter(|delay| !delay.is_zero())
+    }
+
+    /// This function counts a request to the given model as sent.
+    fn record(&mut self, model: &str) {
+        if !is_free(model) {
+            return;
+        }
+
+        self.recent.push_back(Instant::now());
+        *self.daily.today(SystemTime::now()) += 1;
+        // The count is merely a way of avoiding requests bound to fail, so failing to keep it is
+        // no reason to fail the request.
+        drop(self.daily.save());
+    }
+}
+
+/// This structure holds the quota of the API, shared by the network worker sending requests and
+/// the interface telling the player that they are held back.
+#[derive(Clone)]
+pub(crate) struct Throttle(Arc<Mutex<Quota>>);
+
+impl Throttle {
+    /// This function waits until a request to the given model would go through, and counts it as
+    /// sent. If it would have to wait for too long, it's given up on instead, failing with the time
+    /// it would have to wait for, and so is it as soon as the given cancellation flag is raised.
+    pub(crate) fn admit(&self, model: &str, cancelled: &AtomicBool) -> Result<(), CompletionError> {
+        loop {
+            let delay = self.lock().delay(model);
+            match delay {
+                Some(delay) if delay > MAX_WAIT => return Err(CompletionError::Throttled(delay)),
+                Some(delay) if !self.pause(delay, cancelled) => {
+                    return Err(CompletionError::Cancelled)
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+
+        self.lock().record(model);
+
+        Ok(())
+    }
+
+    /// This function returns how much longer the request being held back has to wait for, if one
+    /// is.
+    pub(crate) fn held(&self) -> Option<Duration> {
+        self.lock()
+            .held
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|left| !left.is_zero())
+    }
+
+    /// This function locks the quota, even if a thread panicked while holding it, since it's always
+    /// left in a usable state.
+    fn lock(&self) -> MutexGuard<'_, Quota> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// This function creates the quota of the API, with the given number of requests to free
+    /// models the account may make a day, if known.
+    pub(crate) fn new(daily_limit: Option<u32>) -> Self {
+        Self(Arc::new(Mutex::new(Quota {
+            blocked: None,
+            daily: Daily::load(),
+            daily_limit,
+            held: None,
+            recent: VecDeque::new(),
+            window: None,
+        })))
+    }
+
+    /// This function takes in the rate limit headers of a response with the given status code. A
+    /// response turning down the request for having sent too many of them holds back every request
+    /// until the time the API asks to wait for, or until its quota is reset.
+    pub(crate) fn observe(&self, status: u16, headers: &HeaderMap) {
+        let now = Instant::now();
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+        let mut quota = self.lock();
+
+        if let (Some(remaining), Some(reset)) =
+            (header("x-ratelimit-remaining"), header("x-ratelimit-reset"))
+        {
+            quota.window = Some((remaining, now + reset_in(reset)));
+        }
+
+        if status == 429 {
+            let reset = quota
+                .window
+                .map(|(_, reset)| reset.saturating_duration_since(now));
+            quota.blocked = header("retry-after")
+                .map(Duration::from_secs)
+                .or(reset)
+                .map(|delay| now + delay);
+        }
+    }
+
+    /// This function holds back the request being made for the given time, letting the interface
+    /// know about it in the meantime. The wait is cut short once the given cancellation flag is
+    /// raised, and it returns whether it waited for the whole time.
+    pub(crate) fn pause(&self, delay: Duration, cancelled: &AtomicBool) -> bool {
+        self.lock().held = Some(Instant::now() + delay);
+        let waited = doze(delay, cancelled);
+        self.lock().held = None;
+
+        waited
+    }
+}
+
+/// This function returns whether the given model costs nothing to use, going by its `:free`
+/// suffix, which is what the quota for free models of the OpenRouter API applies to.
+fn is_free(model: &str) -> bool {
+    model.ends_with(":free")
+}
+
+/// This function returns the location of the file the requests of the day are kept in.
+fn path() -> Option<PathBuf> {
+    paths::state_dir().map(|dir| dir.join("quota.toml"))
+}
+
+/// This function returns the time left until the quota is reset, told by the API as a timestamp,
+/// which the OpenRouter API gives in milliseconds since the Unix epoch, and other APIs in seconds
+/// since the Unix epoch or in seconds from now.
+fn reset_in(reset: u64) -> Duration {
+    let now = since_epoch(SystemTime::now());
+
+    if reset >= 1_000_000_000_000 {
+        Duration::from_millis(reset).saturating_sub(now)
+    } else if reset >= 1_000_000_000 {
+        Duration::from_secs(reset).saturating_sub(now)
+    } else {
+        Duration::from_secs(reset)
+    }
+}
+
+/// This function returns the time elapsed since the Unix epoch at the given time.
+fn since_epoch(time: SystemTime) -> Duration {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default()
+}
@@ -0,0 +1,474 @@
+//! This module holds the worker thread all network I/O happens in, so that the terminal interface
+//! never freezes while waiting for an answer.
+//!
+//! The interface hands jobs over to the worker through a channel, and gets back events through
+//! another one, each event tagged with the ticket of the job it belongs to.
+
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, sleep};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use ureq::Agent;
+
+use crate::account::Account;
+use crate::backend::{self, Piece, ResponseBackend};
+use crate::catalog::{Filter, Model, ModelCatalog};
+use crate::chat::{Request, Usage};
+use crate::game::RandomResult;
+use crate::mood::Tone;
+use crate::secret::ApiKey;
+use crate::settings::{Backend, Settings, DEFAULT_MODEL};
+use crate::throttle::Throttle;
+
+/// This constant holds the longest the worker thread sleeps for at once while waiting, so that it
+/// notices soon enough when the job it's waiting for is cancelled.
+const NAP: Duration = Duration::from_millis(100);
+
+/// This structure holds an answer from the backend, along with what's known of how it was produced.
+pub(crate) struct Answer {
+    /// This field contains whether the answer was generated, by an LLM or by an external command,
+    /// rather than taken from the canned lines or the bank of answers.
+    generated: bool,
+    /// This field contains the model the answer was produced by, be it the one asked for or one
+    /// fallen back to, if it went through an LLM.
+    model: Option<String>,
+    /// This field contains the note about how the answer was produced, if there's anything worth
+    /// noting.
+    notice: Option<String>,
+    /// This field contains the answer itself.
+    text: String,
+    /// This field contains the tone the answer was delivered in, if it came back as structured
+    /// output.
+    tone: Option<Tone>,
+    /// This field contains the number of tokens the answer took up, and its cost if known, if it
+    /// went through an LLM.
+    usage: Option<Usage>,
+}
+
+impl Answer {
+    /// This function returns whether the answer was generated, by an LLM or by an external command.
+    pub(crate) const fn is_generated(&self) -> bool {
+        self.generated
+    }
+
+    /// This function returns the model the answer was produced by, if it went through an LLM.
+    pub(crate) fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// This function returns the note about how the answer was produced, if there's any.
+    pub(crate) fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// This function returns the answer itself.
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// This function returns the tone the answer was delivered in, if it's known.
+    pub(crate) const fn tone(&self) -> Option<Tone> {
+        self.tone
+    }
+
+    /// This function returns the number of tokens the answer took up, if it went through an LLM.
+    pub(crate) const fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+}
+
+/// This enumeration holds what the worker did once the spend cap of the session was reached.
+pub(crate) enum Capped {
+    /// This variant is used when the worker went offline, as there's no free model to switch over
+    /// to.
+    Offline,
+    /// This variant is used when the model asked for isn't free, and the given free model is the
+    /// one to switch over to.
+    Switched(String),
+    /// This variant is used when the model asked for is free already, but the models to fall back
+    /// to that aren't were dropped.
+    Trimmed,
+    /// This variant is used when neither the model asked for nor any of the models to fall back to
+    /// cost anything, so there was nothing to do.
+    Unchanged,
+}
+
+/// This enumeration holds the events the worker sends back while working on a job.
+pub(crate) enum Event {
+    /// This variant is used when the status of the account behind the API key has been fetched,
+    /// which is nothing if the API has no account endpoints or there's no API key.
+    Account(Result<Option<Account>>),
+    /// This variant is used when the backend is done answering, successfully or not.
+    Answer(Result<Answer>),
+    /// This variant is used when the worker is done making sure no more money is spent.
+    Capped(Capped),
+    /// This variant is used when the catalog of models has been fetched.
+    Models(Result<Arc<ModelCatalog>>),
+    /// This variant is used for every piece of a streamed answer, as soon as it comes in.
+    Piece(String),
+    /// This variant is used when a streamed answer starts over, so that the pieces that came in
+    /// before are to be thrown away.
+    Restart,
+}
+
+/// This enumeration holds the jobs the worker can be handed over.
+pub(crate) enum Job {
+    /// This variant is used to fetch the status of the account behind the API key.
+    Account,
+    /// This variant is used to make sure no more money is spent once the spend cap is reached, by
+    /// dropping the models to fall back to that aren't free, and either switching the given model
+    /// over to a free one if it isn't free, or going offline if there's none.
+    Cap(String),
+    /// This variant is used to ask the backend for an answer to the result of a game.
+    Complete(Box<Request>, RandomResult),
+    /// This variant is used to fetch the catalog of models available through the API.
+    Models,
+    /// This variant is used to tell the backend that the given answer was shown in the voice of
+    /// the persona with the given identifier.
+    Shown(String, String),
+}
+
+/// This structure holds a job along with what the worker needs to know to track it.
+struct Order {
+    /// This field contains the flag raised by the interface when it's no longer interested in the
+    /// job.
+    cancelled: Arc<AtomicBool>,
+    /// This field contains the job itself.
+    job: Job,
+    /// This field contains the ticket the events of the job are tagged with.
+    ticket: Ticket,
+}
+
+/// This structure represents the ticket identifying one of the jobs handed over to the worker.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Ticket(u64);
+
+/// This structure holds the channels to the worker thread, along with the cancellation flags of
+/// the jobs still in flight and the quota of the API the worker holds requests back by.
+pub(crate) struct Worker {
+    /// This field contains the events of jobs still in flight that came in while polling for
+    /// another job, kept until they are polled for.
+    backlog: HashMap<Ticket, VecDeque<Event>>,
+    /// This field contains the cancellation flags of the jobs that haven't finished yet.
+    cancellations: HashMap<Ticket, Arc<AtomicBool>>,
+    /// This field contains the number of tokens taken up by the answers thrown away since they
+    /// were no longer waited for, until they are taken.
+    discarded: Vec<Usage>,
+    /// This field contains the channel events are received through.
+    events: Receiver<(Ticket, Event)>,
+    /// This field contains the number of the next ticket to hand out.
+    next_ticket: u64,
+    /// This field contains the channel jobs are handed over through.
+    orders: Sender<Order>,
+    /// This field contains the quota of the API, shared with the worker thread.
+    throttle: Throttle,
+}
+
+impl Worker {
+    /// This function raises the cancellation flag of a job, so that the worker skips it if it
+    /// hasn't started it yet, and gives up on it as soon as it can if it has, be it while waiting
+    /// to retry a request or while reading an answer. Events of cancelled jobs are discarded.
+    pub(crate) fn cancel(&mut self, ticket: Ticket) {
+        for event in self.backlog.remove(&ticket).into_iter().flatten() {
+            self.discard(event);
+        }
+        if let Some(cancelled) = self.cancellations.remove(&ticket) {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// This function throws away an event no longer waited for, keeping the number of tokens taken
+    /// up by the answer in it, if any, since it's paid for all the same.
+    fn discard(&mut self, event: Event) {
+        if let Event::Answer(Ok(answer)) = event {
+            self.discarded.extend(answer.usage());
+        }
+    }
+
+    /// This function returns how much longer the worker is holding back the request it's working
+    /// on, so as not to exceed the quota of the API, if it is.
+    pub(crate) fn held(&self) -> Option<Duration> {
+        self.throttle.held()
+    }
+
+    /// This function waits for up to the given time for the next event of the given job. The events
+    /// of other jobs still in flight that come in in the meantime are kept for when they are polled
+    /// for, and those of cancelled jobs are discarded.
+    pub(crate) fn poll(&mut self, ticket: Ticket, timeout: Duration) -> Result<Option<Event>> {
+        let deadline = Instant::now() + timeout;
+        let mut event = self.backlog.get_mut(&ticket).and_then(VecDeque::pop_front);
+
+        while event.is_none() {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(timeout) {
+                Ok((received, received_event)) if received == ticket => {
+                    event = Some(received_event);
+                }
+                Ok((received, received_event)) => {
+                    if self.cancellations.contains_key(&received) {
+                        self.backlog
+                            .entry(received)
+                            .or_default()
+                            .push_back(received_event);
+                    } else {
+                        self.discard(received_event);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => return Ok(None),
+                Err(RecvTimeoutError::Disconnected) => bail!("the network worker has stopped"),
+            }
+        }
+
+        if matches!(
+            event,
+            Some(Event::Account(_) | Event::Answer(_) | Event::Capped(_) | Event::Models(_))
+        ) {
+            drop(self.backlog.remove(&ticket));
+            drop(self.cancellations.remove(&ticket));
+        }
+
+        Ok(event)
+    }
+
+    /// This function tells the backend that the given answer was shown in the voice of the persona
+    /// with the given identifier, for those backends that keep track of the answers shown.
+    pub(crate) fn shown(&mut self, persona: &str, answer: &str) -> Result<()> {
+        let ticket = self.submit(Job::Shown(persona.to_owned(), answer.to_owned()))?;
+        drop(self.cancellations.remove(&ticket));
+
+        Ok(())
+    }
+
+    /// This function spawns the worker thread with the backend selected in the settings the game
+    /// was started with, and a single agent shared by all requests so that connections get reused.
+    pub(crate) fn spawn(settings: &Settings) -> Self {
+        let agent = agent();
+        let throttle = Throttle::new(settings.account.as_ref().and_then(Account::free_quota));
+        let backend = backend::from_settings(settings, agent.clone(), throttle.clone());
+        let base_url = settings.base_url.clone();
+        let api_key = match settings.backend {
+            Backend::OpenRouter(ref api_key) => api_key.clone(),
+            Backend::Bank(_) | Backend::Command(_) | Backend::Mock | Backend::Offline => None,
+        };
+        let (orders, received_orders) = mpsc::channel();
+        let (sender, events) = mpsc::channel();
+
+        drop(thread::spawn(move || {
+            work(
+                &received_orders,
+                &sender,
+                backend,
+                &agent,
+                &base_url,
+                api_key.as_ref(),
+            );
+        }));
+
+        Self {
+            backlog: HashMap::new(),
+            cancellations: HashMap::new(),
+            discarded: Vec::new(),
+            events,
+            next_ticket: 0,
+            orders,
+            throttle,
+        }
+    }
+
+    /// This function hands a job over to the worker, and returns the ticket its events will be
+    /// tagged with.
+    pub(crate) fn submit(&mut self, job: Job) -> Result<Ticket> {
+        let ticket = Ticket(self.next_ticket);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.next_ticket += 1;
+
+        self.orders
+            .send(Order {
+                cancelled: Arc::clone(&cancelled),
+                job,
+                ticket,
+            })
+            .map_err(|err| anyhow!("the network worker has stopped: {err}"))?;
+        drop(self.cancellations.insert(ticket, cancelled));
+
+        Ok(ticket)
+    }
+
+    /// This function takes the number of tokens taken up by each of the answers thrown away since
+    /// the last time, since they were no longer waited for.
+    pub(crate) fn take_discarded(&mut self) -> Vec<Usage> {
+        mem::take(&mut self.discarded)
+    }
+}
+
+/// This function creates the agent requests to the API are made through, which doesn't treat
+/// unsuccessful status codes as errors, so that their bodies and headers can be inspected. Reading
+/// a response is given up on once it has taken too long, so that a stalled one can't hold up the
+/// jobs after it, since cancellations are only checked between reads.
+pub(crate) fn agent() -> Agent {
+    Agent::config_builder()
+        .http_status_as_error(false)
+        .timeout_connect(Some(Duration::from_secs(10)))
+        .timeout_recv_body(Some(Duration::from_secs(120)))
+        .timeout_recv_response(Some(Duration::from_secs(60)))
+        .build()
+        .new_agent()
+}
+
+/// This function sleeps for the given time on the worker thread, waking up every so often to give
+/// up early once the given cancellation flag is raised. It returns whether it slept for the whole
+/// time.
+pub(crate) fn doze(delay: Duration, cancelled: &AtomicBool) -> bool {
+    let deadline = Instant::now() + delay;
+
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        sleep(left.min(NAP));
+    }
+}
+
+/// This function returns the free model to switch over to once the spend cap is reached, which is
+/// the default model if it's free, or else the first free model putting out text in the catalog.
+fn free_model(catalog: &ModelCatalog) -> Option<String> {
+    catalog
+        .get(DEFAULT_MODEL)
+        .filter(|default| default.is_free())
+        .or_else(|| {
+            catalog
+                .filtered(Filter::Free)
+                .into_iter()
+                .find(|free| free.outputs_text())
+        })
+        .map(|free| free.id().to_owned())
+}
+
+/// This function returns the catalog of models available through the API, fetching it only the
+/// first time around. A catalog coming from a stale cache is not kept, so that the next time
+/// around the API gets another chance to answer.
+fn fetch_catalog(
+    catalog: &mut Option<Arc<ModelCatalog>>,
+    agent: &Agent,
+    base_url: &str,
+) -> Result<Arc<ModelCatalog>> {
+    if let Some(ref catalog) = *catalog {
+        return Ok(Arc::clone(catalog));
+    }
+
+    let fetched = Arc::new(ModelCatalog::fetch(agent, base_url)?);
+    if !fetched.is_stale() {
+        *catalog = Some(Arc::clone(&fetched));
+    }
+
+    Ok(fetched)
+}
+
+/// This function makes up the loop of the worker thread, working on jobs one at a time until the
+/// interface hangs up.
+fn work(
+    orders: &Receiver<Order>,
+    events: &Sender<(Ticket, Event)>,
+    mut backend: Box<dyn ResponseBackend>,
+    agent: &Agent,
+    base_url: &str,
+    api_key: Option<&ApiKey>,
+) {
+    let mut catalog = None;
+
+    while let Ok(order) = orders.recv() {
+        if order.cancelled.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        let event = match order.job {
+            Job::Account => Event::Account(
+                api_key
+                    .map(|api_key| Account::fetch(agent, base_url, api_key))
+                    .transpose()
+                    .map(Option::flatten)
+                    .map_err(Into::into),
+            ),
+            Job::Cap(model) => {
+                // Without a catalog, only the models with the `:free` suffix are known to be free.
+                let catalog = fetch_catalog(&mut catalog, agent, base_url).ok();
+                let is_free = |id: &str| {
+                    id.ends_with(":free")
+                        || catalog
+                            .as_ref()
+                            .and_then(|catalog| catalog.get(id))
+                            .is_some_and(Model::is_free)
+                };
+                let trimmed = backend.retain_fallbacks(&is_free);
+                Event::Capped(if is_free(&model) {
+                    if trimmed {
+                        Capped::Trimmed
+                    } else {
+                        Capped::Unchanged
+                    }
+                } else if let Some(free) = catalog.as_deref().and_then(free_model) {
+                    Capped::Switched(free)
+                } else {
+                    backend = backend::offline();
+                    Capped::Offline
+                })
+            }
+            Job::Complete(mut request, result) => {
+                request.cancel_with(Arc::clone(&order.cancelled));
+                let answer = backend.stream(&request, result, &mut |piece| {
+                    let event = match piece {
+                        Piece::Restart => Event::Restart,
+                        Piece::Text(text) => Event::Piece(text.to_owned()),
+                    };
+                    if !order.cancelled.load(Ordering::Relaxed)
+                        && events.send((order.ticket, event)).is_err()
+                    {
+                        order.cancelled.store(true, Ordering::Relaxed);
+                    }
+                });
+                let model = backend.model();
+                let model = model.as_deref().unwrap_or_else(|| request.model());
+                let usage = backend.usage().map(|mut usage| {
+                    if usage.cost().is_none() {
+                        let prices = fetch_catalog(&mut catalog, agent, base_url)
+                            .ok()
+                            .and_then(|catalog| catalog.get(model)?.prices())
+                            .filter(|&(prompt, completion)| prompt >= 0.0 && completion >= 0.0);
+                        if let Some(prices) = prices {
+                            usage.price(prices);
+                        }
+                    }
+                    usage
+                });
+                let model = usage.is_some().then(|| model.to_owned());
+                Event::Answer(answer.map(|text| Answer {
+                    generated: backend.is_generated(),
+                    model,
+                    notice: backend.notice(),
+                    text,
+                    tone: backend.tone(),
+                    usage,
+                }))
+            }
+            Job::Models => Event::Models(fetch_catalog(&mut catalog, agent, base_url)),
+            Job::Shown(persona, answer) => {
+                backend.shown(&persona, &answer);
+                continue;
+            }
+        };
+
+        if events.send((order.ticket, event)).is_err() {
+            break;
+        }
+    }
+}